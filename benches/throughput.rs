@@ -0,0 +1,291 @@
+//! Throughput of the interpreter
+//!
+//! Plays the bundled stories and synthetic loops headlessly, and reports the instructions per second,
+//! the allocations per instruction, the speed-up of sharing a story whose instructions are already decoded, and the slowest opcodes.
+//! Run with `cargo bench`, or `cargo bench -- name` to only play the stories whose name contains `name`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+use common::pack;
+
+/// Counts the allocations
+struct Counting;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The number of iterations of the synthetic loops
+const LOOPS: u16 = 20000;
+
+// Layout of the synthetic stories
+const OBJ: usize = 0x220;
+const TEXT: usize = 0x2a0;
+const PARSE: usize = 0x2c0;
+const DICT: usize = 0x300;
+
+/// The z-chars of lowercase text
+fn zchars(text: &str) -> Vec<u8> {
+    text.bytes()
+        .map(|char| if char == b' ' { 0 } else { char - b'a' + 6 })
+        .collect()
+}
+
+/// A version 5 story running `body` in a loop, with `routine` and `string` after the code
+///
+/// `body` can call the routine and print the string with the packed addresses it gets.
+fn story(body: impl Fn(u16, u16) -> Vec<u8>, routine: &[u8], string: &str) -> Vec<u8> {
+    let mut data = common::story(common::Layout {
+        dict: DICT,
+        obj: OBJ,
+        stat: DICT,
+        ..common::LAYOUT
+    });
+
+    // "open the box", and the dictionary of these words
+    data[TEXT] = 20;
+    data[TEXT + 1] = 12;
+    data[TEXT + 2..TEXT + 14].copy_from_slice(b"open the box");
+    data[PARSE] = 4;
+    data[DICT..DICT + 5].copy_from_slice(&[1, b',', 9, 0, 3]);
+    for (i, word) in ["box", "open", "the"].iter().enumerate() {
+        let mut zchars = zchars(word);
+        zchars.resize(9, 5);
+        let addr = DICT + 5 + 9 * i;
+        data[addr..addr + 6].copy_from_slice(&pack(zchars));
+    }
+
+    // the code is small, the routine and the string follow it
+    let after = common::LAYOUT.code + 0x100;
+    let routine_addr = (after / 4) as u16;
+    let string_addr = routine_addr + (routine.len() as u16).div_ceil(4);
+    let mut code = body(routine_addr, string_addr);
+    // inc_chk g0 LOOPS ?done, jump loop, done: quit
+    code.extend(&[0xc5, 0x4f, 0x10]);
+    code.extend(&LOOPS.to_be_bytes());
+    code.push(0xc5);
+    let offset = -(code.len() as i16 + 3) + 2;
+    code.push(0x8c);
+    code.extend(&offset.to_be_bytes());
+    code.push(0xba);
+    assert!(code.len() <= 0x100);
+    data.extend(&code);
+    data.resize(after, 0);
+    data.extend(routine);
+    data.resize(string_addr as usize * 4, 0);
+    data.extend(&pack(zchars(string)));
+    data
+}
+
+fn synthetic() -> Vec<(&'static str, Vec<u8>, String)> {
+    vec![
+        (
+            // add g1 3 -> g1, mul g1 7 -> g2, and g2 255 -> g2
+            "arithmetic",
+            story(
+                |_, _| {
+                    vec![
+                        0x54, 0x11, 3, 0x11, 0x56, 0x11, 7, 0x12, 0x49, 0x12, 0xff, 0x12,
+                    ]
+                },
+                &[],
+                "",
+            ),
+            String::new(),
+        ),
+        (
+            // call_2s routine g1 -> g2, routine: add l1 l2 -> sp, ret_popped
+            "call",
+            story(
+                |routine, _| {
+                    let mut code = vec![0xd9, 0x2f];
+                    code.extend(&routine.to_be_bytes());
+                    code.extend(&[0x11, 0x12]);
+                    code
+                },
+                &[2, 0x74, 1, 2, 0, 0xb8],
+                "",
+            ),
+            String::new(),
+        ),
+        (
+            // print_paddr string
+            "print",
+            story(
+                |_, string| {
+                    let mut code = vec![0x8d];
+                    code.extend(&string.to_be_bytes());
+                    code
+                },
+                &[],
+                "the quick brown fox jumps over the lazy dog ",
+            ),
+            String::new(),
+        ),
+        (
+            // tokenise TEXT PARSE
+            "tokenise",
+            story(
+                |_, _| {
+                    let mut code = vec![0xfb, 0x0f];
+                    code.extend(&(TEXT as u16).to_be_bytes());
+                    code.extend(&(PARSE as u16).to_be_bytes());
+                    code
+                },
+                &[],
+                "",
+            ),
+            String::new(),
+        ),
+    ]
+}
+
+fn bundled() -> Vec<(&'static str, Vec<u8>, String)> {
+    let chars = |chars: &str| chars.chars().map(|char| format!("{}\n", char)).collect();
+    let etude = format!(
+        "3\n6\n7\n.\n8\n{}.\n13\n14\n \n \n \n.\n",
+        chars("äöüÄÖÜß»«ëïÿËÏáéíóúýÁÉÍÓÚÝàèìòùÀÈÌÒÙâêîôûÂÊÎÔÛåÅøØãñõÃÑÕæÆçÇþðÞÐ£œŒ¡¿€")
+    );
+    let gntests = chars("123!\"#$%&'()*+,-./0123456789:;<=>@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~ 5 0");
+    vec![
+        (
+            "czech.z5",
+            include_bytes!("../zcode/czech/czech.z5").to_vec(),
+            String::new(),
+        ),
+        (
+            "czech.z8",
+            include_bytes!("../zcode/czech/czech.z8").to_vec(),
+            String::new(),
+        ),
+        (
+            "gntests.z5",
+            include_bytes!("../zcode/gntests/gntests.z5").to_vec(),
+            gntests,
+        ),
+        (
+            "etude.z5",
+            include_bytes!("../zcode/etude/etude.z5").to_vec(),
+            etude,
+        ),
+        (
+            "strictz.z5",
+            include_bytes!("../zcode/strictz/strictz.z5").to_vec(),
+            "n\n \n".to_string(),
+        ),
+    ]
+}
+
+/// The shortest time of a few replays of the stories given by `story`, and the allocations of one
+fn time(story: impl Fn() -> rzif::Story, walkthrough: &str) -> (Duration, u64) {
+    let mut best = Duration::MAX;
+    let mut allocations = 0;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_millis(500) || best == Duration::MAX {
+        let story = story();
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let time = Instant::now();
+        let transcript = rzif::replay(story, walkthrough, 1);
+        let time = time.elapsed();
+        allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        drop(transcript);
+        best = best.min(time);
+    }
+    (best, allocations)
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`
+    let filter = env::args().skip(1).find(|arg| !arg.starts_with("--"));
+    let stories = bundled()
+        .into_iter()
+        .chain(synthetic())
+        .filter(|story| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| story.0.contains(filter.as_str()))
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "{:<12} {:>12} {:>10} {:>10} {:>13}",
+        "story", "instructions", "time", "Minstr/s", "allocs/instr"
+    );
+    let mut profiles = Vec::new();
+    let mut caches = Vec::new();
+    for (name, story, walkthrough) in stories.iter() {
+        let profile = rzif::profile(story.clone(), walkthrough, 1);
+        // each game decodes the instructions anew
+        let (time, allocations) = time(|| story.clone().into(), walkthrough);
+        let instructions = profile.instructions;
+        println!(
+            "{:<12} {:>12} {:>8.2}ms {:>10.2} {:>13.3}",
+            name,
+            instructions,
+            time.as_secs_f64() * 1000.0,
+            instructions as f64 / time.as_secs_f64() / 1e6,
+            allocations as f64 / instructions as f64,
+        );
+        profiles.push((name, profile));
+
+        // the games share the instructions decoded by the first one
+        let shared = rzif::Story::from(story.clone());
+        rzif::replay(shared.clone(), walkthrough, 1);
+        let (warm, _) = self::time(|| shared.clone(), walkthrough);
+        caches.push((name, time, warm));
+    }
+
+    println!(
+        "\n{:<12} {:>10} {:>10} {:>8}",
+        "story", "new", "shared", "speed-up"
+    );
+    for (name, new, shared) in caches {
+        println!(
+            "{:<12} {:>8.2}ms {:>8.2}ms {:>7.2}x",
+            name,
+            new.as_secs_f64() * 1000.0,
+            shared.as_secs_f64() * 1000.0,
+            new.as_secs_f64() / shared.as_secs_f64(),
+        );
+    }
+
+    for (name, profile) in profiles {
+        let total = profile
+            .opcodes
+            .iter()
+            .map(|opcode| opcode.2)
+            .sum::<Duration>();
+        println!("\n{}: slowest opcodes", name);
+        for &(opcode, count, time) in profile.opcodes.iter().take(8) {
+            println!(
+                "  {:<16} {:>10} {:>9.1}ns {:>6.1}%",
+                opcode,
+                count,
+                time.as_nanos() as f64 / count as f64,
+                time.as_secs_f64() / total.as_secs_f64() * 100.0,
+            );
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/*
+ * C interface of rzif, a z-machine interpreter
+ *
+ * Build the library with `cargo build --release`, and link with target/release/librzif.
+ * rzif_run plays a game like the `main` function of the Rust crate, calling back the functions
+ * of a RzifCallbacks structure instead of the methods of the `Interface` trait: see its
+ * documentation for what each callback must do.
+ *
+ * Texts are given as UTF-8 bytes with their length, without a terminating zero.
+ */
+
+#ifndef RZIF_H
+#define RZIF_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The configuration of the interface, as `Config` */
+typedef struct RzifConfig {
+    bool status;
+    bool split;
+    bool fixed_default;
+    bool color;
+    bool bold;
+    bool italic;
+    bool fixed;
+    bool timed;
+    uint16_t width;
+    uint16_t height;
+    uint8_t default_foreground;
+    uint8_t default_background;
+    uint16_t true_foreground;
+    uint16_t true_background;
+    bool picture;
+    bool graphics;
+    bool undo;
+    bool sound;
+    bool transparency;
+    /* 0 never, 1 once, 2 always, 3 quit, 4 pedantic, 5 forgiving, 6 frotz */
+    uint8_t error_level;
+    bool has_seed;
+    uint64_t seed;
+    /* 0 lcg, 1 xorshift */
+    uint8_t generator;
+} RzifConfig;
+
+/* Called during an input with the text typed so far. Returns true if the input must stop. */
+typedef bool (*RzifInterrupt)(void *data, const uint8_t *text, size_t len);
+/* Called during the input of a char. Returns true if the input must stop. */
+typedef bool (*RzifInterruptChar)(void *data);
+
+/*
+ * The callbacks to the interface, with the `user` pointer given to each one
+ *
+ * A null callback does nothing, or fails for the ones returning a result.
+ */
+typedef struct RzifCallbacks {
+    void *user;
+    void (*write_screen)(void *user, const uint8_t *text, size_t len, bool fixed);
+    void (*write_transcript)(void *user, const uint8_t *text, size_t len);
+    void (*write_command)(void *user, const uint8_t *text, size_t len);
+    /* Returns 1 if the char can be printed, plus 2 if it can be read */
+    uint8_t (*check_unicode)(void *user, uint32_t c);
+    void (*status)(void *user, const uint8_t *text, size_t len);
+    bool (*window_font)(void *user, uint16_t font);
+    void (*window_color)(void *user, uint16_t foreground, uint16_t background);
+    void (*window_style)(void *user, uint16_t style);
+    void (*window_set)(void *user, uint16_t window);
+    void (*window_buffer)(void *user, uint16_t buffer);
+    void (*window_split)(void *user, uint16_t lines);
+    void (*window_cursor_set)(void *user, uint16_t x, uint16_t y);
+    void (*window_cursor_get)(void *user, uint16_t *x, uint16_t *y);
+    void (*window_erase)(void *user, uint16_t window);
+    void (*window_line)(void *user);
+    /*
+     * Reads a line into `buffer`, up to `capacity` bytes, and sets `len` to its length.
+     * Every `time` tenths of seconds (if not 0), `interrupt` must be called with `data`.
+     * Returns the terminating char.
+     */
+    uint32_t (*read)(void *user, const uint32_t *terminating, size_t terminating_len,
+                     const uint8_t *preload, size_t preload_len, uint16_t maxlen, uint16_t time,
+                     RzifInterrupt interrupt, void *data, uint8_t *buffer, size_t capacity,
+                     size_t *len);
+    /* Reads a char. Every `time` tenths of seconds (if not 0), `interrupt` must be called with `data`. */
+    uint32_t (*read_char)(void *user, uint16_t time, RzifInterruptChar interrupt, void *data);
+    /* Reads a line of the command file into `buffer`, up to `capacity` bytes. Returns its length. */
+    size_t (*read_file)(void *user, uint8_t *buffer, size_t capacity);
+    void (*bleep)(void *user, uint16_t bleep);
+    void (*sound)(void *user, uint16_t number, uint16_t effect, uint16_t volume);
+    bool (*save)(void *user, const uint8_t *data, size_t len);
+    /* Returns the saved data and sets `len`, or returns null. The data must stay valid until the next callback. */
+    const uint8_t *(*restore)(void *user, size_t *len);
+    void (*restore_failed)(void *user, const uint8_t *cause, size_t len);
+    bool (*save_aux)(void *user, const uint8_t *name, size_t name_len, const uint8_t *data, size_t len);
+    /* As `restore`, for the auxiliary file `name` */
+    const uint8_t *(*restore_aux)(void *user, const uint8_t *name, size_t name_len, size_t *len);
+    /* `cause` is one of the RZIF_CAUSE_* below */
+    void (*error)(void *user, uint32_t cause, const uint8_t *text, size_t len);
+    bool (*running)(void *user);
+    /* Returns true and sets the new size of the screen if it was resized since the last call */
+    bool (*resized)(void *user, uint16_t *width, uint16_t *height);
+} RzifCallbacks;
+
+/* A game to play */
+typedef struct RzifVm RzifVm;
+
+#define RZIF_QUIT 0
+#define RZIF_STOPPED 1
+#define RZIF_FATAL 2
+
+/* The causes of the errors given to the `error` callback, as `Cause`. New causes are added at the end. */
+#define RZIF_CAUSE_QUIT 0
+#define RZIF_CAUSE_BAD_VER 1
+#define RZIF_CAUSE_TOO_SHORT 2
+#define RZIF_CAUSE_STATIC_OUT 3
+#define RZIF_CAUSE_WRITE_OUT 4
+#define RZIF_CAUSE_READ_OUT 5
+#define RZIF_CAUSE_DIV_BY_ZERO 6
+#define RZIF_CAUSE_MISSING_OPERAND 7
+#define RZIF_CAUSE_BAD_OPCODE 8
+#define RZIF_CAUSE_STACK_UNDERFLOW 9
+#define RZIF_CAUSE_NO_VAR 10
+#define RZIF_CAUSE_NO_LOCAL 11
+#define RZIF_CAUSE_NO_LOCAL_IN_MAIN 12
+#define RZIF_CAUSE_MAIN_RETURNED 13
+#define RZIF_CAUSE_PC_OUT 14
+#define RZIF_CAUSE_TOO_MANY_LOCALS 15
+#define RZIF_CAUSE_TEXT_BUFFER_OUT 16
+#define RZIF_CAUSE_TERMINATING_OUT 17
+#define RZIF_CAUSE_STR_OUT 18
+#define RZIF_CAUSE_ALPHABET_OUT 19
+#define RZIF_CAUSE_UNICODE_OUT 20
+#define RZIF_CAUSE_BAD_UNICODE_CHAR 21
+#define RZIF_CAUSE_BAD_ZSCII_CHAR 22
+#define RZIF_CAUSE_NESTED_ABBR 23
+#define RZIF_CAUSE_ABBR_INCOMPLETE_ZSCII 24
+#define RZIF_CAUSE_BAD_OBJ 25
+#define RZIF_CAUSE_NOT_CHILD_OF_PARENT 26
+#define RZIF_CAUSE_BAD_ATTR 27
+#define RZIF_CAUSE_BAD_PROP 28
+#define RZIF_CAUSE_GET_LONG_PROP 29
+#define RZIF_CAUSE_PUT_LONG_PROP 30
+#define RZIF_CAUSE_NO_PROP 31
+#define RZIF_CAUSE_BAD_INPUT_STREAM 32
+#define RZIF_CAUSE_BAD_OUTPUT_STREAM 33
+#define RZIF_CAUSE_NO_OUTPUT_S3 34
+#define RZIF_CAUSE_OUTPUT_S3_OVERFLOW 35
+#define RZIF_CAUSE_BAD_COLOR 36
+#define RZIF_CAUSE_SAVE_INTERRUPT 37
+#define RZIF_CAUSE_STOPPED 38
+#define RZIF_CAUSE_NO_STORY 39
+#define RZIF_CAUSE_BAD_CHECKSUM 40
+#define RZIF_CAUSE_ABBR_OUT 41
+#define RZIF_CAUSE_DICT_OUT 42
+#define RZIF_CAUSE_OBJECTS_OUT 43
+#define RZIF_CAUSE_PROP_OUT 44
+#define RZIF_CAUSE_BAD_PATCH 45
+
+/* Writes the default configuration */
+void rzif_default_config(RzifConfig *config);
+/* Makes a game from a storyfile or a Blorb file, copied with the callbacks. `config` can be null. */
+RzifVm *rzif_new(const uint8_t *story, size_t len, const RzifConfig *config,
+                 const RzifCallbacks *callbacks);
+/*
+ * Plays the game from its start until its end. Returns RZIF_QUIT, RZIF_STOPPED or RZIF_FATAL,
+ * also returned if the interpreter panicked.
+ */
+int32_t rzif_run(RzifVm *vm);
+/* Writes the fatal error of the last game into `buffer`. Returns its length, 0 if there was none. */
+size_t rzif_error(const RzifVm *vm, uint8_t *buffer, size_t capacity);
+/* Frees a game */
+void rzif_free(RzifVm *vm);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
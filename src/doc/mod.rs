@@ -10,6 +10,8 @@ The fonts are:
 3. a [character graphics font](http://inform-fiction.org/zmachine/standards/z1point1/sect16.html)
 4. a Courier-style font with fixed pitch
 
+When [`Config::graphics`](crate::Config::graphics) is set, the interpreter handles font 3 itself: your interface isn't asked about it, and the chars 32 to 126 printed with it are replaced by unicode box-drawing, block, arrow and runic characters, written in fixed pitch.
+
 # Colors
 ## True colors
 True colors are 16 bits colors in the fromat `$$0bbbbbgggggrrrrr` (a 0 bit followed by 5 bits for blue, 5 for green and 5 for red).
@@ -41,6 +43,16 @@ The screen should be cleared and the text cursor placed at the top left.
 
 > Note: the version is the first byte of the story file.
 
+## Capabilities
+In version 5 and later, the game asks for the features it wants in the header, and the interpreter tells which ones are missing. They are given by [`Config`](crate::Config):
+- pictures, with `picture`
+- undo, with `undo`. Without it, `@save_undo` tells the game that undo isn't available.
+- colors, with `color`
+- sound effects, with `sound`. The bleeps are always available, and the other sound effects are given to [`Interface::sound`](crate::Interface::sound), which plays the sounds of the Blorb file. The routine called at the end of a sound isn't supported.
+- a transparent background color, with `transparency`
+
+This interpreter doesn't support the mouse nor the menus of version 6, so it always tells the game they are missing.
+
 # Windows
 The screen is divided into a lower and an upper window and at any given time one of these is selected. (Initially it is the lower window.) The game uses the set_window opcode to select one of the two. Each window has its own cursor position at which text is printed. Operations in the upper window do not move the cursor of the lower. Whenever the upper window is selected, its cursor position is reset to the top left. Selecting, or re-sizing, the upper window does not change the screen's appearance.
 
@@ -81,6 +93,13 @@ Erasing window -1 clears the whole screen to the background colour of the lower
 ## Erasing a line
 Using erase_line in the upper window should erase the current line from the cursor position to the right-hand edge, clearing it to background colour. (Even if the text style is Reverse Video the new blank space should not have reversed colours.)
 
+# Input
+While the player types a line, the interface echoes the chars to the screen. When the input is terminated by a newline, the interpreter prints the newline; when it is terminated by another char or stopped by an interrupt routine, the cursor is left at the end of the text typed so far.
+
+If an interrupt routine of a timed input prints something, the interpreter prints the text typed so far again after it, so that the player can go on typing. A line read from a command file is printed as if it had been typed.
+
+Single chars read with read_char are never echoed.
+
 # Bleeps
 This interpreter support only one sound effect: a beep or bell sound, which we shall call a "bleep".
 Bleep number 1 is a high-pitched bleep, number 2 a low-pitched one
@@ -94,6 +113,10 @@ The command file contains all the player inputs.
 
 ## Save
 A save file contains the state of the game. It is encoded in [Quetzal](http://inform-fiction.org/zmachine/standards/quetzal/index.html) format in this interpreter.
+The state of the random number generator is kept in an additional `Rand` chunk (8 bytes of state followed by 2 bytes of counter), which other interpreters ignore.
+
+## Auxiliary files
+In version 5 and later, `@save` and `@restore` can also write and read a table of bytes, to keep data such as high scores or preferences between games. The filename suggested by the game is made of 1 to 8 letters or digits, optionally followed by a dot and 1 to 3 more: this interpreter lowercases it and adds the `.aux` extension if there is none, so the same file is found whatever the case used by the game. If the game doesn't suggest a name, the name given to [`Interface::save_aux`](crate::Interface::save_aux) and [`Interface::restore_aux`](crate::Interface::restore_aux) is empty and the player should be asked for one.
 
 # Text
 **TODO: arrows, fn, numeric**
@@ -102,6 +125,8 @@ The interpreter is required to be able to print representations of every defined
 
 An interpreter is not required to have suitable letter-forms for printing Unicode characters $0100 to $FFFF. (It may, if it chooses, allow the user to configure certain fonts for certain Unicode ranges; but this is not required.) If a Unicode character must be printed which an interpreter has no letter-form for, a question mark should be printed instead.
 
+This interpreter asks your interface which characters it can print with [`Interface::check_unicode`](crate::Interface::check_unicode), and replaces the others by the textual equivalents below (or by a question mark). The same is done for the text written to a table by output stream 3, when a character has no ZSCII code.
+
 ## Unicode characters
 |Unicode code (hex)|Name|Character|Textual Equivalent|
 |---|-----------|-|--|
@@ -0,0 +1,38 @@
+use std::{env::Args, fs, process};
+
+pub fn main(name: &str, mut args: Args) {
+    let (path, format) = match (args.next(), args.next()) {
+        (Some(path), format) => (path, format.unwrap_or_else(|| "text".to_string())),
+        _ => {
+            eprintln!("Usage: {} extract path/to/storyfile [text|json]", name);
+            process::exit(2);
+        }
+    };
+    let story = match fs::read(&path) {
+        Ok(story) => story,
+        Err(err) => {
+            eprintln!("Error opening the file {}: {}", path, err);
+            process::exit(2);
+        }
+    };
+
+    let texts = match rzif::extract(&story) {
+        Ok(texts) => texts,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    };
+    match format.as_str() {
+        "text" => {
+            for text in texts.iter() {
+                println!("{}", text);
+            }
+        }
+        "json" => print!("{}", rzif::to_json(&texts)),
+        _ => {
+            eprintln!("Unknown format {}, expected text or json", format);
+            process::exit(2);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::err::*;
+use crate::{err::*, interface::*};
 use std::time::UNIX_EPOCH;
 
 pub fn eq(a: u16, b: u16) -> bool {
@@ -47,30 +47,30 @@ pub fn art(a: u16, b: u16) -> u16 {
     }
 }
 
-pub struct Random(u64, u16);
+pub struct Random {
+    generator: Generator,
+    seeded: bool,
+    state: u64,
+    count: u16,
+}
 
 impl Random {
     pub fn rand(&mut self, range: u16) -> u16 {
         match range {
             0 => {
-                self.0 = random().into();
-                self.1 = 0;
+                self.state = if self.seeded { self.next() } else { clock() };
+                self.count = 0;
             }
             1..=0x7fff => {
-                return match *self {
-                    Random(0, 0) => random(),
-                    Random(_, 0) => {
-                        self.0 = self.0.wrapping_mul(0x015a_4e35).wrapping_add(1);
-                        (self.0 >> 16) as u16
-                    }
-                    Random(_, _) => {
-                        self.0 += 1;
-                        if self.0 as u16 == self.1 {
-                            self.0 = 0;
-                            self.1
-                        } else {
-                            self.0 as u16 - 1
-                        }
+                return if self.count == 0 {
+                    self.next() as u16
+                } else {
+                    self.state += 1;
+                    if self.state as u16 == self.count {
+                        self.state = 0;
+                        self.count
+                    } else {
+                        self.state as u16 - 1
                     }
                 } % range
                     + 1
@@ -78,27 +78,60 @@ impl Random {
             _ => {
                 let seed = !range + 1;
                 if seed < 1000 {
-                    self.0 = 0;
-                    self.1 = seed;
+                    self.state = 0;
+                    self.count = seed;
                 } else {
-                    self.0 = seed.into();
-                    self.1 = 0;
+                    self.state = seed.into();
+                    self.count = 0;
                 };
             }
         }
         0
     }
+
+    pub fn save(&self) -> (u64, u16) {
+        (self.state, self.count)
+    }
+
+    pub fn restore(&mut self, saved: (u64, u16)) {
+        self.state = saved.0;
+        self.count = saved.1;
+    }
+
+    fn next(&mut self) -> u64 {
+        match self.generator {
+            Generator::Lcg => {
+                self.state = self.state.wrapping_mul(0x015a_4e35).wrapping_add(1);
+                self.state >> 16
+            }
+            Generator::Xorshift => {
+                if self.state == 0 {
+                    // xorshift never leaves the zero state
+                    self.state = 0x9e37_79b9_7f4a_7c15;
+                }
+                self.state ^= self.state << 13;
+                self.state ^= self.state >> 7;
+                self.state ^= self.state << 17;
+                self.state >> 32
+            }
+        }
+    }
 }
 
-pub fn init() -> Random {
-    Random(0, 0)
+pub fn init(config: &Config) -> Random {
+    Random {
+        generator: config.generator,
+        seeded: config.seed.is_some(),
+        state: config.seed.unwrap_or_else(clock),
+        count: 0,
+    }
 }
 
-fn random() -> u16 {
+fn clock() -> u64 {
     UNIX_EPOCH
         .elapsed()
         .unwrap_or_else(|err| err.duration())
-        .subsec_nanos() as u16
+        .as_nanos() as u64
 }
 
 #[test]
@@ -142,8 +175,8 @@ fn test_log() {
 
 #[test]
 fn test_rand() {
-    random();
-    let mut rand = init();
+    clock();
+    let mut rand = init(&DEFAULT);
     assert!(rand.rand(10) - 1 < 10);
     assert_eq!(rand.rand(1), 1);
     assert_eq!(rand.rand(0xff88), 0);
@@ -159,3 +192,35 @@ fn test_rand() {
     assert_eq!(a, b);
     assert_eq!(rand.rand(0), 0);
 }
+
+#[test]
+fn test_seeded() {
+    for &generator in [Generator::Lcg, Generator::Xorshift].iter() {
+        let config = Config {
+            seed: Some(0),
+            generator,
+            ..DEFAULT
+        };
+        let mut a = init(&config);
+        let mut b = init(&config);
+        for _ in 0..10 {
+            assert_eq!(a.rand(100), b.rand(100));
+        }
+        assert_eq!(a.rand(0), 0);
+        assert_eq!(b.rand(0), 0);
+        assert_eq!(a.rand(0x7fff), b.rand(0x7fff));
+
+        let saved = a.save();
+        let a = (0..10).map(|_| a.rand(0x7fff)).collect::<Vec<_>>();
+        b.restore(saved);
+        let b = (0..10).map(|_| b.rand(0x7fff)).collect::<Vec<_>>();
+        assert_eq!(a, b);
+
+        let mut c = init(&Config {
+            seed: Some(1),
+            ..config
+        });
+        let c = (0..10).map(|_| c.rand(0x7fff)).collect::<Vec<_>>();
+        assert_ne!(a, c);
+    }
+}
@@ -1,12 +1,50 @@
 use crate::{err::*, mem::Mem, state::State};
+use std::{
+    fmt,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        OnceLock,
+    },
+};
 
-pub struct Instr {
+#[derive(Clone, Copy)]
+pub struct Instr<T = u16> {
     pub count: u8,
     pub opcode: u8,
-    pub operands: Vec<u16>,
+    pub operands: Operands<T>,
     pub end: usize,
 }
 
+/// The operands of an instruction, stored inline (there are at most 8)
+#[derive(Clone, Copy)]
+pub struct Operands<T> {
+    len: u8,
+    data: [T; 8],
+}
+
+/// An operand as encoded, before reading the variables
+#[derive(Clone, Copy, Default)]
+pub struct Operand {
+    var: bool,
+    value: u16,
+}
+
+/// The decoded instructions of static and high memory, shared by the games of a [`Story`](crate::Story)
+///
+/// Instructions in dynamic memory can be changed by the game, they are decoded each time.
+pub struct Cache {
+    stat: usize,
+    /// for each address from `stat`, 1 + the index of its instruction, or 0
+    index: Box<[AtomicU32]>,
+    /// the instructions, in chunks of `CHUNK` that never move once allocated
+    chunks: Box<[OnceLock<Chunk>]>,
+    len: AtomicU32,
+}
+
+const CHUNK: usize = 256;
+type Chunk = Box<[OnceLock<Instr<Operand>>]>;
+
 impl Instr {
     pub fn store(&self, mem: &mut Mem, state: &mut State, val: u16) -> Result<(), Error> {
         let var = *fatal(mem.get(self.end).ok_or_else(|| err(Cause::PcOut, (0, 0))))?;
@@ -38,7 +76,12 @@ impl Instr {
             match offset {
                 0 | 1 => state.ret(mem, offset as u16)?,
                 2..=0x1fff => state.pc = addr + offset,
-                _ => state.pc = addr - (!offset & 0x1fff) - 1, // ERROR substract with overflow
+                _ => {
+                    state.pc = fatal(
+                        addr.checked_sub((!offset & 0x1fff) + 1)
+                            .ok_or_else(|| err(Cause::PcOut, (0, 0))),
+                    )?
+                }
             }
         } else {
             // continue
@@ -52,21 +95,148 @@ impl Instr {
     }
 }
 
-pub fn decode(mem: &Mem, state: &mut State, mut addr: usize) -> Result<Instr, Error> {
+impl<T: Copy + Default> Instr<T> {
+    fn map<U, F>(&self, mut op: F) -> Result<Instr<U>, Error>
+    where
+        U: Copy + Default,
+        F: FnMut(T) -> Result<U, Error>,
+    {
+        let mut operands = Operands::default();
+        for &operand in self.operands.iter() {
+            operands.push(op(operand)?);
+        }
+        Ok(Instr {
+            count: self.count,
+            opcode: self.opcode,
+            operands,
+            end: self.end,
+        })
+    }
+}
+
+impl<T: Copy + Default> Operands<T> {
+    fn push(&mut self, operand: T) {
+        self.data[self.len as usize] = operand;
+        self.len += 1;
+    }
+}
+
+impl<T: Copy + Default> Default for Operands<T> {
+    fn default() -> Operands<T> {
+        Operands {
+            len: 0,
+            data: [T::default(); 8],
+        }
+    }
+}
+
+impl<T> Deref for Operands<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.data[..self.len as usize]
+    }
+}
+
+impl<T: PartialEq> PartialEq<Vec<T>> for Operands<T> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        **self == other[..]
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Operands<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Cache {
+    fn get(&self, addr: usize) -> Option<Instr<Operand>> {
+        let slot = addr
+            .checked_sub(self.stat)
+            .and_then(|i| self.index.get(i))?;
+        let i = (slot.load(Ordering::Acquire) as usize).checked_sub(1)?;
+        self.chunks[i / CHUNK].get()?[i % CHUNK].get().copied()
+    }
+
+    fn insert(&self, addr: usize, instr: Instr<Operand>) {
+        let Some(slot) = addr.checked_sub(self.stat).and_then(|i| self.index.get(i)) else {
+            return;
+        };
+        // games decoding the same instruction at once each insert it, the last one is kept
+        let i = self.len.fetch_add(1, Ordering::Relaxed) as usize;
+        let Some(chunk) = self.chunks.get(i / CHUNK) else {
+            return;
+        };
+        let chunk = chunk.get_or_init(|| (0..CHUNK).map(|_| OnceLock::new()).collect());
+        if chunk[i % CHUNK].set(instr).is_ok() {
+            slot.store(i as u32 + 1, Ordering::Release);
+        }
+    }
+}
+
+pub fn cache(mem: &Mem) -> Cache {
+    let stat = mem.loadw(0x0e).unwrap() as usize;
+    let len = mem.len() - stat;
+    Cache {
+        stat,
+        index: (0..len).map(|_| AtomicU32::new(0)).collect(),
+        chunks: (0..len.div_ceil(CHUNK)).map(|_| OnceLock::new()).collect(),
+        len: AtomicU32::new(0),
+    }
+}
+
+pub fn decode(mem: &Mem, state: &mut State, addr: usize) -> Result<Instr, Error> {
+    let cache = mem.cache();
+    let instr = match cache.get(addr) {
+        Some(instr) => instr,
+        None => {
+            let instr = read(mem, addr)?;
+            cache.insert(addr, instr);
+            instr
+        }
+    };
+    instr.map(|operand| {
+        if operand.var {
+            state.get_var(mem, operand.value)
+        } else {
+            Ok(operand.value)
+        }
+    })
+}
+
+/// Decodes an instruction without reading the variables
+///
+/// The operands that are variables are `None`.
+pub fn decode_static(mem: &Mem, addr: usize) -> Result<Instr<Option<u16>>, Error> {
+    read(mem, addr)?.map(|operand| {
+        Ok(if operand.var {
+            None
+        } else {
+            Some(operand.value)
+        })
+    })
+}
+
+fn read(mem: &Mem, mut addr: usize) -> Result<Instr<Operand>, Error> {
+    let op = |type_: u8, value: u16| Operand {
+        var: type_ == 2,
+        value,
+    };
     let top = *mem.get(addr).ok_or_else(|| err(Cause::PcOut, (0, 0)))?;
     if mem[0] >= 5 && top == 0xbe {
         // ext
         let opcode = *mem.get(addr + 1).ok_or_else(|| err(Cause::PcOut, (0, 0)))?;
         let types = *mem.get(addr + 2).ok_or_else(|| err(Cause::PcOut, (0, 0)))?;
-        let mut operands = Vec::new();
+        let mut operands = Operands::default();
         addr += 3;
         for i in (0..4).rev() {
             let type_ = (types >> (i * 2)) & 0x03;
             if type_ == 3 {
                 break;
             }
-            let operand = parse_op(mem, state, addr, type_)?;
-            operands.push(operand.0);
+            let operand = parse_op(mem, addr, type_)?;
+            operands.push(op(type_, operand.0));
             addr = operand.1
         }
         Ok(Instr {
@@ -81,7 +251,7 @@ pub fn decode(mem: &Mem, state: &mut State, mut addr: usize) -> Result<Instr, Er
                 // var
                 let opcode = top & 0x1f;
                 addr += 1;
-                let mut operands = Vec::new();
+                let mut operands = Operands::default();
                 let mut types = vec![*mem.get(addr).ok_or_else(|| err(Cause::PcOut, (0, 0)))?];
                 addr += 1;
                 let count = if top & 0x20 == 0 {
@@ -100,8 +270,8 @@ pub fn decode(mem: &Mem, state: &mut State, mut addr: usize) -> Result<Instr, Er
                         if type_ == 3 {
                             break;
                         }
-                        let operand = parse_op(mem, state, addr, type_)?;
-                        operands.push(operand.0);
+                        let operand = parse_op(mem, addr, type_)?;
+                        operands.push(op(type_, operand.0));
                         addr = operand.1;
                     }
                 }
@@ -124,28 +294,30 @@ pub fn decode(mem: &Mem, state: &mut State, mut addr: usize) -> Result<Instr, Er
                     Ok(Instr {
                         count: 0,
                         opcode: top & 0x0f,
-                        operands: vec![],
+                        operands: Operands::default(),
                         end: addr + 1,
                     })
                 } else {
                     // 1OP
-                    let operand = parse_op(mem, state, addr + 1, type_ >> 4)?;
+                    let operand = parse_op(mem, addr + 1, type_ >> 4)?;
+                    let mut operands = Operands::default();
+                    operands.push(op(type_ >> 4, operand.0));
                     Ok(Instr {
                         count: 1,
                         opcode: top & 0x0f,
-                        operands: vec![operand.0],
+                        operands,
                         end: operand.1,
                     })
                 }
             }
             _ => {
                 // long
-                let mut operands = Vec::new();
+                let mut operands = Operands::default();
                 addr += 1;
                 for i in 1..3 {
                     let type_ = if top & (0x80 >> i) == 0 { 1 } else { 2 };
-                    let operand = parse_op(mem, state, addr, type_)?;
-                    operands.push(operand.0);
+                    let operand = parse_op(mem, addr, type_)?;
+                    operands.push(op(type_, operand.0));
                     addr = operand.1;
                 }
                 Ok(Instr {
@@ -159,20 +331,16 @@ pub fn decode(mem: &Mem, state: &mut State, mut addr: usize) -> Result<Instr, Er
     }
 }
 
-fn parse_op(mem: &Mem, state: &mut State, addr: usize, type_: u8) -> Result<(u16, usize), Error> {
+fn parse_op(mem: &Mem, addr: usize, type_: u8) -> Result<(u16, usize), Error> {
     match type_ {
         0 => Ok((
             mem.getw(addr).ok_or_else(|| err(Cause::PcOut, (0, 0)))?,
             addr + 2,
         )),
-        1 => Ok((
+        1 | 2 => Ok((
             u16::from(*mem.get(addr).ok_or_else(|| err(Cause::PcOut, (0, 0)))?),
             addr + 1,
         )),
-        2 => {
-            let var = u16::from(*mem.get(addr).ok_or_else(|| err(Cause::PcOut, (0, 0)))?);
-            Ok((state.get_var(&mem, var)?, addr + 1))
-        }
         _ => unreachable!(),
     }
 }
@@ -233,6 +401,19 @@ fn test_decode() {
     assert_eq!(instr.opcode, 0);
     assert_eq!(instr.operands, vec![2, 0xa5, 0x55aa]);
     assert_eq!(instr.end, 0x58);
+
+    // cached, the variables are still read
+    assert!(mem.cache().get(0x4b).is_some());
+    state.set_var(&mut mem, 1, 0x5a).unwrap();
+    let instr = decode(&mem, &mut state, 0x4b).unwrap();
+    assert_eq!(instr.operands, vec![0xf0f0, 2, 0x5a]);
+
+    let instr = decode_static(&mem, 0x4b).unwrap();
+    assert_eq!(instr.operands, vec![Some(0xf0f0), Some(2), None]);
+    assert_eq!(instr.end, 0x51);
+    let instr = decode_static(&mem, 0x51).unwrap();
+    assert_eq!(instr.operands, vec![Some(2), None, Some(0x55aa)]);
+    assert_eq!(instr.end, 0x58);
 }
 
 #[test]
@@ -246,7 +427,7 @@ fn test_store() {
     let instr = Instr {
         count: 0,
         opcode: 0,
-        operands: vec![],
+        operands: Operands::default(),
         end: 0x40,
     };
     instr.store(&mut mem, &mut state, 0x0102).unwrap();
@@ -257,13 +438,13 @@ fn test_store() {
 fn test_branch() {
     let mut data = mem::default();
     data.extend(vec![
-        0x40, 0xc1, 0x50, 0x91, 0x00, 0x3f, 0xf0, 0x01, 0x01, 0x00,
+        0x40, 0xc1, 0x50, 0x91, 0x00, 0x3f, 0xf0, 0x01, 0x01, 0x00, 0xa0, 0x00,
     ]);
     let mut mem = mem::new(data).unwrap();
     let mut instr = Instr {
         count: 0,
         opcode: 0,
-        operands: vec![],
+        operands: Operands::default(),
         end: 0x40,
     };
     let mut state = state::init(&mem);
@@ -298,4 +479,8 @@ fn test_branch() {
     assert_eq!(state.pc, 0x35);
     instr.branch(&mut mem, &mut state, true, false).unwrap();
     assert_eq!(state.pc, 0x47);
+    // before the start of memory
+    instr.end = 0x4a;
+    let err = instr.branch(&mut mem, &mut state, true, false).unwrap_err();
+    assert_eq!(err.cause, Cause::PcOut);
 }
@@ -1,8 +1,62 @@
-use crate::err::*;
-use std::ops::Deref;
+use crate::{err::*, instr};
+use std::{
+    fmt,
+    ops::{Deref, Index},
+    sync::{Arc, OnceLock},
+};
 
+/// A storyfile, to play with [`main`](crate::main)
+///
+/// Cloning it is cheap: the clones share the file and the instructions decoded from its static and high memory, so the games playing them only copy their dynamic memory.
+#[derive(Clone)]
+pub struct Story {
+    file: Arc<[u8]>,
+    cache: Arc<OnceLock<instr::Cache>>,
+}
+
+impl From<Arc<[u8]>> for Story {
+    fn from(file: Arc<[u8]>) -> Story {
+        Story {
+            file,
+            cache: Arc::default(),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Story {
+    fn from(file: Vec<u8>) -> Story {
+        Arc::<[u8]>::from(file).into()
+    }
+}
+
+impl From<&[u8]> for Story {
+    fn from(file: &[u8]) -> Story {
+        Arc::<[u8]>::from(file).into()
+    }
+}
+
+impl fmt::Debug for Story {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Story")
+            .field("len", &self.file.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deref for Story {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.file
+    }
+}
+
+/// The memory of a game
+///
+/// Only the dynamic memory (and the header) is copied for each game: the rest is read from the storyfile, shared by all the games playing it.
 pub struct Mem {
-    mem: Vec<u8>,
+    dynamic: Vec<u8>,
+    story: Story,
     stat: u16,
     v: u8,
 }
@@ -22,9 +76,9 @@ impl Mem {
             4..=5 => addr as usize * 4,
             7 => {
                 let offset = if rout {
-                    get(&self.mem, 0x28)
+                    get(&self.dynamic, 0x28)
                 } else {
-                    get(&self.mem, 0x2a)
+                    get(&self.dynamic, 0x2a)
                 } as usize
                     * 8;
                 addr as usize * 4 + offset
@@ -38,56 +92,91 @@ impl Mem {
         if addr >= self.stat {
             return error(Cause::WriteOut, (addr, 0));
         }
-        self.mem[addr as usize] = data as u8;
+        self.dynamic[addr as usize] = data as u8;
         Ok(())
     }
 
     pub fn storew(&mut self, addr: u16, data: u16) -> Result<(), Error> {
-        if addr + 1 >= self.stat {
+        if addr as usize + 1 >= self.stat as usize {
             return error(Cause::WriteOut, (addr, 0));
         }
-        set(&mut self.mem, addr as usize, data);
+        set(&mut self.dynamic, addr as usize, data);
         Ok(())
     }
 
     pub fn loadb(&self, addr: u16) -> Result<u16, Error> {
-        if addr as usize >= self.mem.len() {
-            return error(Cause::ReadOut, (addr, 0));
+        match self.get(addr as usize) {
+            Some(&byte) => Ok(u16::from(byte)),
+            None => error(Cause::ReadOut, (addr, 0)),
         }
-        Ok(u16::from(self.mem[addr as usize]))
     }
 
     pub fn loadw(&self, addr: u16) -> Result<u16, Error> {
-        if addr as usize + 1 >= self.mem.len() {
-            return error(Cause::ReadOut, (addr, 0));
+        match self.getw(addr as usize) {
+            Some(word) => Ok(word),
+            None => error(Cause::ReadOut, (addr, 0)),
         }
-        Ok(get(&self.mem, addr as usize))
     }
 
     pub fn getw(&self, addr: usize) -> Option<u16> {
-        if addr + 1 < self.mem.len() {
-            Some(get(&self.mem, addr))
-        } else {
-            None
+        Some(u16::from(*self.get(addr)?) << 8 | u16::from(*self.get(addr + 1)?))
+    }
+
+    pub fn get(&self, addr: usize) -> Option<&u8> {
+        match self.dynamic.get(addr) {
+            Some(byte) => Some(byte),
+            None => self.story.get(addr),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.story.len()
+    }
+
+    /// The storyfile as it was loaded, without the changes of the game
+    pub fn story(&self) -> &[u8] {
+        &self.story
+    }
+
+    /// The decoded instructions of static and high memory
+    pub fn cache(&self) -> &instr::Cache {
+        self.story.cache.get_or_init(|| instr::cache(self))
+    }
+
+    /// Replace bytes anywhere in the memory, for the compatibility patches
+    ///
+    /// Patching static or high memory gives this game its own copy of the storyfile, and of its decoded instructions.
+    pub fn patch(&mut self, addr: usize, bytes: &[u8]) {
+        for (addr, &byte) in (addr..).zip(bytes) {
+            match self.dynamic.get_mut(addr) {
+                Some(old) => *old = byte,
+                None => {
+                    Arc::make_mut(&mut self.story.file)[addr] = byte;
+                    self.story.cache = Arc::default();
+                }
+            }
         }
     }
 
     pub fn save(&self) -> Vec<u8> {
-        self.mem[..self.stat as usize].to_vec()
+        self.dynamic[..self.stat as usize].to_vec()
     }
 
     pub fn restore(&mut self, data: &[u8]) {
-        let flags = self.mem[0x11];
-        self.mem[..self.stat as usize].copy_from_slice(data);
-        self.mem[0x11] = flags;
+        let flags = self.dynamic[0x11];
+        self.dynamic[..self.stat as usize].copy_from_slice(data);
+        self.dynamic[0x11] = flags;
     }
 }
 
-impl Deref for Mem {
-    type Target = Vec<u8>;
+impl Index<usize> for Mem {
+    type Output = u8;
 
-    fn deref(&self) -> &Vec<u8> {
-        &self.mem
+    fn index(&self, addr: usize) -> &u8 {
+        match self.dynamic.get(addr) {
+            Some(byte) => byte,
+            None => &self.story[addr],
+        }
     }
 }
 
@@ -95,12 +184,13 @@ fn get(data: &[u8], addr: usize) -> u16 {
     u16::from(data[addr]) << 8 | u16::from(data[addr + 1])
 }
 
-fn set(data: &mut Vec<u8>, addr: usize, value: u16) {
+fn set(data: &mut [u8], addr: usize, value: u16) {
     data[addr] = (value >> 8) as u8;
     data[addr + 1] = value as u8;
 }
 
-pub fn new(data: Vec<u8>) -> Result<Mem, Error> {
+pub fn new(data: impl Into<Story>) -> Result<Mem, Error> {
+    let data = data.into();
     if data.len() < 0x40 {
         return error(Cause::TooShort, (data.len() as u16, 0));
     }
@@ -112,7 +202,14 @@ pub fn new(data: Vec<u8>) -> Result<Mem, Error> {
     if stat as usize > data.len() {
         return error(Cause::StaticOut, (stat, 0));
     }
-    Ok(Mem { mem: data, stat, v })
+    // the header is changed by the interpreter even if it isn't in dynamic memory
+    let dynamic = data[..(stat as usize).max(0x40)].to_vec();
+    Ok(Mem {
+        dynamic,
+        story: data,
+        stat,
+        v,
+    })
 }
 
 #[cfg(test)]
@@ -143,7 +240,8 @@ fn test_addr() {
     data[0x29] = 20;
     data[0x2b] = 30;
     let mut mem = Mem {
-        mem: data,
+        dynamic: data.clone(),
+        story: data.into(),
         stat: 0,
         v: 1,
     };
@@ -167,7 +265,9 @@ fn test_mem() {
     data[0x0f] = 0x45;
     data.extend(vec![0; 0x10]);
     let mut mem = new(data.clone()).unwrap();
-    assert_eq!(mem.mem, data);
+    assert_eq!(mem.dynamic, data[..0x45]);
+    assert_eq!(mem.story(), data);
+    assert_eq!(mem.len(), 0x50);
     assert_eq!(mem.stat, 0x45);
     assert_eq!(mem.v, 1);
     mem.storeb(0x42, 0x10).unwrap();
@@ -177,6 +277,7 @@ fn test_mem() {
     assert_eq!(mem[0x42], 0x10);
     assert_eq!(mem[0x43], 0x20);
     assert_eq!(mem[0x44], 0x30);
+    assert_eq!(mem.storew(0xffff, 0).unwrap_err().cause, Cause::WriteOut);
 }
 
 #[test]
@@ -190,5 +291,30 @@ fn test_save_restore() {
     data[0x41] = 8;
     data[0x42] = 9;
     mem.restore(&data[..0x43]);
-    assert_eq!(mem.mem, data);
+    assert_eq!(mem.dynamic, data[..0x43]);
+    assert_eq!(mem[0x43], 4);
+}
+
+#[test]
+fn test_shared() {
+    let mut data = default();
+    data[0x0f] = 0x42;
+    data.extend(vec![1, 2, 3, 4]);
+    let story = Story::from(data);
+    let mut first = new(story.clone()).unwrap();
+    let second = new(story.clone()).unwrap();
+    first.storeb(0x41, 7).unwrap();
+    assert_eq!((first[0x41], second[0x41], story[0x41]), (7, 2, 2));
+    assert_eq!(first.getw(0x41), Some(0x0703));
+    assert_eq!(first.get(0x44), None);
+    assert!(Arc::ptr_eq(&first.story.file, &second.story.file));
+    assert!(std::ptr::eq(first.cache(), second.cache()));
+
+    // patching static memory copies the storyfile and the cache
+    first.patch(0x41, &[8, 9]);
+    assert_eq!((first[0x41], first[0x42], second[0x42]), (8, 9, 3));
+    assert!(!Arc::ptr_eq(&first.story.file, &story.file));
+    assert!(Arc::ptr_eq(&second.story.file, &story.file));
+    assert!(!std::ptr::eq(first.cache(), second.cache()));
+    assert!(std::ptr::eq(second.cache(), story.cache.get().unwrap()));
 }
@@ -1,4 +1,5 @@
 use crate::{err::*, machine, mem::Mem, rout, *};
+use std::time::Duration;
 
 pub struct State {
     stack: Vec<Vec<u16>>,
@@ -6,6 +7,8 @@ pub struct State {
     call: Vec<Frame>,
     vars: u16,
     interrupt: Option<usize>,
+    /// the executions and time of each opcode, when profiling
+    pub counts: Option<Vec<(u64, Duration)>>,
 }
 
 pub struct Saved {
@@ -13,10 +16,12 @@ pub struct Saved {
     pub stack: Vec<Vec<u16>>,
     pub pc: usize,
     pub call: Vec<Frame>,
+    pub rand: Option<(u64, u16)>,
 }
 
 #[derive(Clone)]
 pub struct Frame {
+    pub routine: Option<usize>,
     pub ret: usize,
     pub local: Vec<u16>,
     pub args: u16,
@@ -24,7 +29,7 @@ pub struct Frame {
 }
 
 impl State {
-    pub fn save(&self, mem: &Mem) -> Result<Saved, Error> {
+    pub fn save(&self, mem: &Mem, rand: &alu::Random) -> Result<Saved, Error> {
         if self.interrupt.is_some() {
             return error(Cause::SaveInterrupt, (0, 0));
         }
@@ -33,14 +38,18 @@ impl State {
             stack: self.stack.clone(),
             pc: self.pc,
             call: self.call.clone(),
+            rand: Some(rand.save()),
         })
     }
 
-    pub fn restore(&mut self, mem: &mut Mem, saved: &Saved) {
+    pub fn restore(&mut self, mem: &mut Mem, rand: &mut alu::Random, saved: &Saved) {
         mem.restore(&saved.mem);
         self.stack = saved.stack.clone();
         self.pc = saved.pc;
         self.call = saved.call.clone();
+        if let Some(saved) = saved.rand {
+            rand.restore(saved);
+        }
     }
 
     pub fn get_var(&mut self, mem: &Mem, var: u16) -> Result<u16, Error> {
@@ -58,7 +67,7 @@ impl State {
                 .local
                 .get(var as usize - 1)
                 .ok_or_else(|| err(Cause::NoLocal, (var - 1, 0)))?),
-            0x10..=0xff => mem.loadw(self.vars + 2 * (var - 0x10)),
+            0x10..=0xff => mem.loadw(self.vars.wrapping_add(2 * (var - 0x10))),
             _ => error(Cause::NoVar, (var, 0)),
         }
     }
@@ -75,7 +84,7 @@ impl State {
                     .get_mut(var as usize - 1)
                     .ok_or_else(|| err(Cause::NoLocal, (var - 1, 0)))? = data
             }
-            0x10..=0xff => mem.storew(self.vars + 2 * (var - 0x10), data)?,
+            0x10..=0xff => mem.storew(self.vars.wrapping_add(2 * (var - 0x10)), data)?,
             _ => return error(Cause::NoVar, (var, 0)),
         }
         Ok(())
@@ -119,6 +128,7 @@ impl State {
         local[..len].clone_from_slice(&args[..len]);
         self.stack.push(Vec::new());
         self.call.push(Frame {
+            routine: Some(addr),
             ret: self.pc,
             local,
             args: args.len() as u16,
@@ -168,6 +178,24 @@ impl State {
             .args)
     }
 
+    /// The depth and routine of the current frame
+    pub fn frame(&self) -> (usize, Option<usize>) {
+        let routine = self.call.last().and_then(|frame| frame.routine);
+        (self.call.len(), routine)
+    }
+
+    /// The routines being executed, from the innermost
+    pub fn calls(&self) -> Vec<Call> {
+        self.call
+            .iter()
+            .rev()
+            .map(|frame| Call {
+                routine: frame.routine,
+                locals: frame.local.clone(),
+            })
+            .collect()
+    }
+
     pub fn interrupt<I: Interface>(
         &mut self,
         addr: usize,
@@ -192,16 +220,8 @@ impl State {
         let interrupt = self.call.len();
         self.interrupt = Some(interrupt);
         if let Err(err) = self.call(mem, addr, Vec::new(), false) {
-            match config.error {
-                ErrorLevel::Never => {}
-                ErrorLevel::Once => {
-                    let id = err.cause as usize;
-                    if !err_said[id] {
-                        interface.error(err);
-                        err_said[id] = true;
-                    }
-                }
-                ErrorLevel::Always | ErrorLevel::Quit => interface.error(err),
+            if let Err(err) = machine::report(err, interface, config, err_said) {
+                interface.error(err);
             }
         }
 
@@ -229,11 +249,12 @@ pub fn init(mem: &Mem) -> State {
         call: Vec::new(),
         vars: mem.loadw(0x0c).unwrap(),
         interrupt: None,
+        counts: None,
     }
 }
 
 #[cfg(test)]
-use crate::mem;
+use crate::{interface, mem};
 
 #[test]
 fn test_init() {
@@ -259,6 +280,7 @@ fn test_save_restore() {
         stack: vec![vec![1, 2], vec![3, 4]],
         pc: 10,
         call: vec![Frame {
+            routine: Some(30),
             ret: 20,
             local: vec![5, 6],
             args: 2,
@@ -266,9 +288,15 @@ fn test_save_restore() {
         }],
         vars: 0,
         interrupt: None,
+        counts: None,
     };
 
-    let mut save = state.save(&mem).unwrap();
+    let mut rand = alu::init(&Config {
+        seed: Some(0x1234),
+        ..interface::DEFAULT
+    });
+    let mut save = state.save(&mem, &rand).unwrap();
+    let number = rand.rand(0x7fff);
     mem.storew(0x40, 0).unwrap();
     mem.storew(0x42, 0).unwrap();
     state.stack = vec![];
@@ -282,9 +310,10 @@ fn test_save_restore() {
     assert_eq!(save.call[0].ret, 20);
     assert_eq!(save.call[0].local, vec![5, 6]);
     assert_eq!(save.call[0].args, 2);
-    assert_eq!(save.call[0].store, true);
+    assert!(save.call[0].store);
 
-    state.restore(&mut mem, &save);
+    state.restore(&mut mem, &mut rand, &save);
+    assert_eq!(rand.rand(0x7fff), number);
     save.mem = vec![];
     save.stack = vec![];
     save.pc = 0;
@@ -298,7 +327,7 @@ fn test_save_restore() {
     assert_eq!(state.call[0].ret, 20);
     assert_eq!(state.call[0].local, vec![5, 6]);
     assert_eq!(state.call[0].args, 2);
-    assert_eq!(state.call[0].store, true);
+    assert!(state.call[0].store);
 }
 
 #[test]
@@ -354,7 +383,7 @@ fn test_call_ret() {
     assert_eq!(state.call[0].ret, 0);
     assert_eq!(state.call[0].local, vec![]);
     assert_eq!(state.call[0].args, 3);
-    assert_eq!(state.call[0].store, false);
+    assert!(!state.call[0].store);
 
     state.call(&mut mem, 0x41, vec![1, 2, 3], false).unwrap();
     assert_eq!(state.pc, 0x42);
@@ -363,7 +392,7 @@ fn test_call_ret() {
     assert_eq!(state.call[1].ret, 0x41);
     assert_eq!(state.call[1].local, vec![1, 2]);
     assert_eq!(state.call[1].args, 3);
-    assert_eq!(state.call[1].store, false);
+    assert!(!state.call[1].store);
 
     state.ret(&mut mem, 0).unwrap();
     assert_eq!(state.pc, 0x41);
@@ -372,7 +401,7 @@ fn test_call_ret() {
     assert_eq!(state.call[0].ret, 0);
     assert_eq!(state.call[0].local, vec![]);
     assert_eq!(state.call[0].args, 3);
-    assert_eq!(state.call[0].store, false);
+    assert!(!state.call[0].store);
 
     state.set_var(&mut mem, 0, 0x5678).unwrap();
     state.set_var(&mut mem, 0, 0x9abc).unwrap();
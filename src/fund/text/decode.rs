@@ -66,7 +66,7 @@ impl Text {
                 // abbreviation
                 1 => {
                     let abbr = 32 * (data[i - 1] - 1) + char;
-                    let addr = mem.word(mem.loadw(self.abbr + 2 * u16::from(abbr))?);
+                    let addr = self.abbr_addr(mem, abbr.into())?;
                     zscii.append(&mut trace(self.zscii(mem, addr, true), Trace::Abbr(addr))?.0);
                     state.0 = 0;
                 }
@@ -93,14 +93,33 @@ impl Text {
     }
 
     pub fn decode(&self, mem: &Mem, addr: usize) -> Result<(String, usize), Error> {
+        let (zscii, end) = self.zscii(mem, addr, false)?;
+        Ok((self.decode_zscii(mem, &zscii)?, end))
+    }
+
+    /// The address of an abbreviation
+    pub fn abbr_addr(&self, mem: &Mem, abbr: u16) -> Result<usize, Error> {
+        let addr = mem
+            .getw(self.abbr as usize + 2 * abbr as usize)
+            .ok_or_else(|| err(Cause::AbbrOut, (self.abbr, 0)))?;
+        Ok(mem.word(addr))
+    }
+
+    /// Decodes an abbreviation, as if it was used in a string
+    pub fn abbr(&self, mem: &Mem, abbr: u16) -> Result<String, Error> {
+        let addr = self.abbr_addr(mem, abbr)?;
+        let zscii = trace(self.zscii(mem, addr, true), Trace::Abbr(addr))?.0;
+        self.decode_zscii(mem, &zscii)
+    }
+
+    fn decode_zscii(&self, mem: &Mem, zscii: &[u8]) -> Result<String, Error> {
         let mut result = String::new();
-        let (zscii, end) = self.zscii(&mem, addr, false)?;
         for &char in zscii.iter() {
             if let Some(char) = self.decode_char(mem, char)? {
                 result.push(char);
             }
         }
-        Ok((result, end))
+        Ok(result)
     }
 
     pub fn decode_char(&self, mem: &Mem, char: u8) -> Result<Option<char>, Error> {
@@ -126,7 +145,9 @@ impl Text {
                     char::from_u32(char.into())
                         .ok_or_else(|| err(Cause::BadUnicodeChar, (char, 0)))?
                 }
-                None => UNICODE[char as usize - 155],
+                None => *UNICODE
+                    .get(char as usize - 155)
+                    .ok_or_else(|| err(Cause::BadZSCIIChar, (char.into(), 0)))?,
             })),
             _ => error(Cause::BadZSCIIChar, (char.into(), 0)),
         }
@@ -149,6 +170,11 @@ fn test_zscii_v1() {
         text.zscii(&mem, 0x40, false).unwrap(),
         (b"0c2E4\rbCd3f\rB1DeF\r".to_vec(), 0x52)
     );
+    assert_eq!(text.decode_char(&mem, 223).unwrap(), Some('¿'));
+    assert_eq!(
+        text.decode_char(&mem, 224).unwrap_err().cause,
+        Cause::BadZSCIIChar
+    );
 }
 
 #[test]
@@ -156,11 +182,11 @@ fn test_zscii_alphabet() {
     let mut data = mem::default();
     data[0x00] = 5;
     data[0x35] = 0x40;
-    data.extend(vec!['1' as u8, '2' as u8]);
+    data.extend(vec![b'1', b'2']);
     data.extend(vec![0; 24]);
-    data.extend(vec!['3' as u8, '4' as u8]);
+    data.extend(vec![b'3', b'4']);
     data.extend(vec![0; 24]);
-    data.extend(vec!['5' as u8, '6' as u8]);
+    data.extend(vec![b'5', b'6']);
     data.extend(vec![0; 24]);
     data.extend(vec![
         0x18, 0xe0, 0x10, 0xc4, 0x1c, 0xa7, 0x14, 0xc1, 0xd4, 0xa5,
@@ -192,6 +218,12 @@ fn test_zscii_abbr() {
         text.zscii(&mem, 0x8c, false).unwrap(),
         (b"Hello World".to_vec(), 0x90)
     );
+    assert_eq!(
+        text.abbr(&mem, 0).unwrap(),
+        text.decode(&mem, 0x84).unwrap().0
+    );
+    assert_eq!(text.abbr(&mem, 96).unwrap_err().cause, Cause::AbbrOut);
+    assert_eq!(super::abbreviations(&mem), 96);
 }
 
 #[test]
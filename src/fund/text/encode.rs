@@ -51,8 +51,17 @@ impl Text {
     pub fn encode(&self, mem: &Mem, addr: u16, len: u16) -> Result<Vec<u16>, Error> {
         let mut bytes = Vec::new();
         if self.v < 3 {
+            // a z-char of the alphabet, or a zscii escape
+            fn push(bytes: &mut Vec<u8>, (alph, char): (u8, u8)) {
+                if alph == 3 {
+                    bytes.extend(&[6, char >> 5, char & 0x1f]);
+                } else {
+                    bytes.push(char);
+                }
+            }
+
             let mut state = (true, (0, 0), 0);
-            for i in addr..addr + len {
+            for i in (0..len).map(|i| addr.wrapping_add(i)) {
                 let prev = state.1;
                 let char = mem.loadb(i)? as u8;
                 state.1 = self.char(mem, char);
@@ -67,7 +76,7 @@ impl Text {
                     _ => unreachable!(),
                 };
                 if prev_alph == state.2 {
-                    bytes.push(prev.1);
+                    push(&mut bytes, prev);
                     continue;
                 }
                 let cur_alph = match (state.1).0 {
@@ -83,13 +92,7 @@ impl Text {
                 } else {
                     bytes.push(shift + 1);
                 }
-                if prev.0 == 4 {
-                    bytes.push(6);
-                    bytes.push(prev.1 >> 5);
-                    bytes.push(prev.1 & 0x1f);
-                } else {
-                    bytes.push(prev.1);
-                }
+                push(&mut bytes, prev);
             }
             let prev = state.1;
             let prev_alph = match prev.0 {
@@ -99,20 +102,14 @@ impl Text {
                 _ => unreachable!(),
             };
             if prev_alph == state.2 {
-                bytes.push(prev.1);
+                push(&mut bytes, prev);
             } else {
                 let shift = (prev_alph + 3 - state.2) % 3;
                 bytes.push(shift + 1);
-                if prev.0 == 4 {
-                    bytes.push(6);
-                    bytes.push(prev.1 >> 5);
-                    bytes.push(prev.1 & 0x1f);
-                } else {
-                    bytes.push(prev.1);
-                }
+                push(&mut bytes, prev);
             }
         } else {
-            for i in addr..addr + len {
+            for i in (0..len).map(|i| addr.wrapping_add(i)) {
                 let char = mem.loadb(i)? as u8;
                 match self.char(mem, char) {
                     (0, char) | (4, char) => bytes.push(char),
@@ -206,6 +203,8 @@ fn test_to_zscii() {
     assert_eq!(mem.loadw(0x46).unwrap(), 0x640d);
     assert_eq!(mem.loadw(0x48).unwrap(), 0x6566);
     assert_eq!(mem.loadw(0x4a).unwrap(), 0x9bdf);
+    // ZSCII 0 is never valid text
+    assert_eq!(text.to_zscii_char(&mem, '\0').unwrap(), 0x3f);
 }
 
 #[test]
@@ -227,11 +226,14 @@ fn test_encode_old() {
     let mut data = mem::default();
     data.extend(b"a0b".to_vec());
     data.extend(b"abc012".to_vec());
+    data.extend(b"a;12;".to_vec());
     let mut mem = mem::new(data).unwrap();
     let header = header::init_test(&mut mem);
     let text = init(&mem, &header).unwrap();
     assert_eq!(text.encode(&mem, 0x40, 3).unwrap(), vec![0x1867, 0x9ca5]);
     assert_eq!(text.encode(&mem, 0x43, 6).unwrap(), vec![0x18e8, 0x94e8]);
+    assert_eq!(text.encode(&mem, 0x49, 2).unwrap(), vec![0x1866, 0x8765]);
+    assert_eq!(text.encode(&mem, 0x4b, 3).unwrap(), vec![0x1509, 0x983b]);
 }
 
 #[test]
@@ -1,4 +1,5 @@
 use crate::{err::*, header::Header, mem::Mem};
+use std::borrow::Cow;
 
 pub struct Text {
     table: Option<usize>,
@@ -10,16 +11,31 @@ pub struct Text {
 static A0: [u8; 26] = *b"abcdefghijklmnopqrstuvwxyz";
 static A1: [u8; 26] = *b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
 static A2: [u8; 26] = *b"\0\r0123456789.,!?_#'\"/\\-:()";
-static A2V1: [u8; 26] = *b"\00123456789.,!?_#'\"/\\<-:()";
+static A2V1: [u8; 26] = *b"\x000123456789.,!?_#'\"/\\<-:()";
 static UNICODE: [char; 69] = [
     'ä', 'ö', 'ü', 'Ä', 'Ö', 'Ü', 'ß', '»', '«', 'ë', 'ï', 'ÿ', 'Ë', 'Ï', 'á', 'é', 'í', 'ó', 'ú',
     'ý', 'Á', 'É', 'Í', 'Ó', 'Ú', 'Ý', 'à', 'è', 'ì', 'ò', 'ù', 'À', 'È', 'Ì', 'Ò', 'Ù', 'â', 'ê',
     'î', 'ô', 'û', 'Â', 'Ê', 'Î', 'Ô', 'Û', 'å', 'Å', 'ø', 'Ø', 'ã', 'ñ', 'õ', 'Ã', 'Ñ', 'Õ', 'æ',
     'Æ', 'ç', 'Ç', 'þ', 'ð', 'Þ', 'Ð', '£', 'œ', 'Œ', '¡', '¿',
 ];
+static FALLBACK: [&str; 69] = [
+    "ae", "oe", "ue", "Ae", "Oe", "Ue", "ss", ">>", "<<", "e", "i", "y", "E", "I", "a", "e", "i",
+    "o", "u", "y", "A", "E", "I", "O", "U", "Y", "a", "e", "i", "o", "u", "A", "E", "I", "O", "U",
+    "a", "e", "i", "o", "u", "A", "E", "I", "O", "U", "a", "A", "o", "O", "a", "n", "o", "A", "N",
+    "O", "ae", "AE", "c", "C", "th", "th", "Th", "Th", "L", "oe", "OE", "!", "?",
+];
+static GRAPHICS: [char; 95] = [
+    ' ', '←', '→', '╱', '╲', ' ', '─', '─', '│', '│', '┴', '┬', '├', '┤', '└', '┌', '┐', '┘', '╰',
+    '╭', '╮', '╯', '▙', '▛', '▜', '▟', '▔', '▁', '▏', '▕', ' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇',
+    '▏', '▎', '▍', '▌', '▋', '█', '▀', '▄', '▌', '▐', '▘', '▝', '▖', '▗', '▚', '▞', '░', '▒', '▓',
+    '▊', '▉', '▐', '↑', '↓', '↕', '□', '?', 'ᚪ', 'ᛒ', 'ᛇ', 'ᛞ', 'ᛖ', 'ᚠ', 'ᚷ', 'ᚻ', 'ᛁ', 'ᛄ', 'ᛦ',
+    'ᛚ', 'ᛗ', 'ᚾ', 'ᚩ', 'ᛈ', 'ᛩ', 'ᚱ', 'ᛋ', 'ᛏ', 'ᚢ', 'ᚡ', 'ᚹ', 'ᛉ', 'ᚣ', 'ᛣ', '↖', '↗', '↘', '↙',
+];
 
+#[allow(unused_imports)]
 pub use self::decode::*;
 mod decode;
+#[allow(unused_imports)]
 pub use self::encode::*;
 mod encode;
 
@@ -48,6 +64,47 @@ pub fn init(mem: &Mem, header: &Header) -> Result<Text, Error> {
     })
 }
 
+/// The number of abbreviations
+pub fn abbreviations(mem: &Mem) -> u16 {
+    match mem[0] {
+        _ if mem.loadw(0x18).unwrap() == 0 => 0,
+        1 => 0,
+        2 => 32,
+        _ => 96,
+    }
+}
+
+/// Replace the chars that aren't `shown` by their textual equivalent, or by a question mark
+pub fn fallback<F: FnMut(char) -> bool>(text: &str, mut shown: F) -> Cow<'_, str> {
+    let mut result: Option<String> = None;
+    for (i, char) in text.char_indices() {
+        if char <= '~' || shown(char) {
+            if let Some(ref mut result) = result {
+                result.push(char);
+            }
+        } else {
+            let result = result.get_or_insert_with(|| text[..i].to_string());
+            match UNICODE.iter().position(|&c| c == char) {
+                Some(i) => result.push_str(FALLBACK[i]),
+                None => result.push('?'),
+            }
+        }
+    }
+    result.map_or(Cow::Borrowed(text), Cow::Owned)
+}
+
+/// Replace the chars 32 to 126 by their [character graphics font](crate::doc#fonts) glyph
+pub fn graphics(text: &str) -> Cow<'_, str> {
+    Cow::Owned(
+        text.chars()
+            .map(|char| match char {
+                ' '..='~' => GRAPHICS[char as usize - 32],
+                _ => char,
+            })
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 use crate::{header, mem};
 
@@ -78,3 +135,17 @@ fn test_init() {
     assert_eq!(text.abbr, 0x100);
     assert_eq!(text.v, 4);
 }
+
+#[test]
+fn test_fallback() {
+    assert!(matches!(fallback("abc", |_| false), Cow::Borrowed("abc")));
+    assert!(matches!(fallback("äöü", |_| true), Cow::Borrowed("äöü")));
+    assert_eq!(fallback("Læti ¿ÿ€", |_| false), "Laeti ?y?");
+    assert_eq!(fallback("Læti ¿ÿ€", |c| c == '€'), "Laeti ?y€");
+}
+
+#[test]
+fn test_graphics() {
+    assert_eq!(graphics("!\"#/0K\\a~"), "←→╱┌┐█↑ᚪ↙");
+    assert_eq!(graphics("\nä"), "\nä");
+}
@@ -65,7 +65,7 @@ impl Input {
         };
         addr += 1;
 
-        let (mut str, char) = match self.current {
+        let (str, char) = match self.current {
             0 => {
                 let mut left = String::new();
                 let addr = addr as usize;
@@ -83,22 +83,45 @@ impl Input {
                 } else {
                     mem.packed(routine, true)
                 };
-                interface.read(terminating, left, max, time, |interface| {
-                    state
-                        .interrupt(
-                            routine, mem, rand, text, out, screen, self, header, obj, dict,
-                            interface, restart, undo, config, err_said,
-                        )
-                        .unwrap_or(0)
-                        != 0
-                })
+                let (str, char) =
+                    interface.read(terminating, left, max, time, |interface, typed| {
+                        out.printed();
+                        let stop = state
+                            .interrupt(
+                                routine, mem, rand, text, out, screen, self, header, obj, dict,
+                                interface, restart, undo, config, err_said,
+                            )
+                            .unwrap_or(0)
+                            != 0;
+                        if !stop && out.printed() {
+                            // the routine wrote over the input line
+                            out.echo(mem, interface, typed);
+                        }
+                        stop
+                    });
+                if char == '\n' {
+                    out.echo(mem, interface, "\n");
+                }
+                (str, char)
+            }
+            1 => {
+                let mut str = interface.read_file();
+                str.truncate(str.trim_end_matches(&['\r', '\n'][..]).len());
+                out.echo(mem, interface, &str);
+                out.echo(mem, interface, "\n");
+                (str, '\n')
             }
-            1 => (interface.read_file(), '\n'),
             _ => unreachable!(),
         };
-        str.truncate(max as usize);
+        let str = str.chars().take(max as usize).collect::<String>();
 
-        let end = text.to_zscii(mem, addr, &str)?;
+        // the game only understands lowercase input, which can have more chars
+        let lower = str
+            .to_lowercase()
+            .chars()
+            .take(max as usize)
+            .collect::<String>();
+        let end = text.to_zscii(mem, addr, &lower)?;
         if self.v < 5 {
             mem.storeb(end, 0)?;
         } else {
@@ -1,9 +1,17 @@
-use crate::{err::*, interface::Interface, mem::Mem, text::Text};
+use crate::{
+    err::*,
+    interface::Interface,
+    mem::Mem,
+    text::{self, Text},
+};
+use std::borrow::Cow;
 
 pub struct Output {
     s1: bool,
     s3: Vec<(u16, u16)>,
     s4: bool,
+    graphics: bool,
+    printed: bool,
 }
 
 impl Output {
@@ -19,15 +27,27 @@ impl Output {
             0 => {
                 if self.s3.is_empty() {
                     // @print_*
+                    let text = if self.graphics {
+                        text::graphics(text)
+                    } else {
+                        Cow::Borrowed(text)
+                    };
                     if self.s1 {
-                        output.write_screen(text, mem[0x11] & 0x02 != 0);
+                        let text = text::fallback(&text, |char| output.check_unicode(char).0);
+                        output.write_screen(&text, self.graphics || mem[0x11] & 0x02 != 0);
+                        self.printed = true;
                     }
                     if mem[0x11] & 0x01 != 0 {
-                        output.write_transcript(text);
+                        output.write_transcript(&text);
                     }
                 } else {
+                    let text = text::fallback(text, |char| {
+                        encode
+                            .to_zscii_char(mem, char)
+                            .map_or(true, |char| char != 0x3f)
+                    });
                     let addr = self.s3.last_mut().unwrap();
-                    addr.1 = encode.to_zscii(mem, addr.1, text)?;
+                    addr.1 = encode.to_zscii(mem, addr.1, &text)?;
                 }
             }
             1 => {
@@ -50,6 +70,24 @@ impl Output {
         Ok(())
     }
 
+    /// Echo the player's input to the screen
+    pub fn echo<I: Interface>(&mut self, mem: &Mem, output: &mut I, text: &str) {
+        if self.s1 {
+            let text = text::fallback(text, |char| output.check_unicode(char).0);
+            output.write_screen(&text, mem[0x11] & 0x02 != 0);
+        }
+    }
+
+    /// Has the game printed something on the screen since the last call ?
+    pub fn printed(&mut self) -> bool {
+        std::mem::replace(&mut self.printed, false)
+    }
+
+    /// Draw the text with the character graphics font
+    pub fn graphics(&mut self, graphics: bool) {
+        self.graphics = graphics;
+    }
+
     pub fn select(&mut self, mem: &mut Mem, stream: u16, table: Option<&u16>) -> Result<(), Error> {
         if stream & 0x8000 == 0 {
             match stream {
@@ -63,7 +101,7 @@ impl Output {
                         return error(Cause::OutputS3Overflow, (0, 0));
                     }
                     let &table = table.ok_or_else(|| err(Cause::MissingOperand, (2, 1)))?;
-                    self.s3.push((table, table + 2));
+                    self.s3.push((table, table.wrapping_add(2)));
                 }
                 4 => self.s4 = true,
                 _ => return error(Cause::BadOutputStream, (stream, 0)),
@@ -80,7 +118,7 @@ impl Output {
                         .s3
                         .pop()
                         .ok_or_else(|| err(Cause::NoOutputS3, (0, 0)))?;
-                    mem.storew(begin, end - begin - 2)?;
+                    mem.storew(begin, end.wrapping_sub(begin).wrapping_sub(2))?;
                 }
                 4 => self.s4 = false,
                 _ => return error(Cause::BadOutputStream, (stream, 0)),
@@ -95,6 +133,8 @@ pub fn init() -> Output {
         s1: true,
         s3: Vec::new(),
         s4: false,
+        graphics: false,
+        printed: false,
     }
 }
 
@@ -112,10 +152,13 @@ impl Interface for Out {
     fn write_command(&mut self, str: &str) {
         self.2 += str;
     }
+    fn check_unicode(&mut self, char: char) -> (bool, bool) {
+        (char != 'æ', true)
+    }
 }
 
 #[cfg(test)]
-use crate::{header, mem, text};
+use crate::{header, mem};
 
 #[test]
 fn test_out() {
@@ -144,11 +187,11 @@ fn test_out() {
     out.select(&mut mem, 0xfffc, None).unwrap();
     assert_eq!(mem.loadb(0x11).unwrap() & 1, 0);
 
-    out.write(&mut mem, &text, &mut output, "C0", 0).unwrap();
+    out.write(&mut mem, &text, &mut output, "Cæ€", 0).unwrap();
     out.write(&mut mem, &text, &mut output, "C1", 1).unwrap();
     out.write(&mut mem, &text, &mut output, "C2", 2).unwrap();
 
-    assert_eq!(&output.0, "A0C0");
+    assert_eq!(&output.0, "A0Cae€");
     assert_eq!(&output.1, "B0B1");
     assert_eq!(&output.2, "B1\nB2\n");
 }
@@ -180,3 +223,75 @@ fn test_s3() {
     assert_eq!(mem.loadw(0x44).unwrap(), 0x01);
     assert_eq!(mem.loadb(0x46).unwrap(), 0x32);
 }
+
+#[test]
+fn test_s3_fallback() {
+    let mut data = mem::default();
+    data[0x00] = 5;
+    data[0x0f] = 0x50;
+    data[0x37] = 0x50;
+    data.extend(vec![0; 0x10]);
+    data.extend(vec![0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x58]);
+    data.extend(vec![0x01, 0x21, 0xaf]);
+    let mut mem = mem::new(data).unwrap();
+    let header = header::init_test(&mut mem);
+    let text = text::init(&mem, &header).unwrap();
+    let mut out = init();
+    let mut output = Out(String::new(), String::new(), String::new());
+
+    out.select(&mut mem, 3, Some(&0x40)).unwrap();
+    out.write(&mut mem, &text, &mut output, "↯æΩ", 0).unwrap();
+    out.select(&mut mem, 0xfffd, None).unwrap();
+
+    assert_eq!(mem.loadw(0x40).unwrap(), 0x04);
+    assert_eq!(mem.loadw(0x42).unwrap(), 0x9b61);
+    assert_eq!(mem.loadw(0x44).unwrap(), 0x653f);
+}
+
+#[test]
+fn test_graphics() {
+    let mut data = mem::default();
+    data[0x0f] = 0x44;
+    data.extend(vec![0; 4]);
+    let mut mem = mem::new(data).unwrap();
+    let header = header::init_test(&mut mem);
+    let text = text::init(&mem, &header).unwrap();
+    let mut out = init();
+    let mut output = Out(String::new(), String::new(), String::new());
+
+    out.select(&mut mem, 2, None).unwrap();
+    out.graphics(true);
+    out.write(&mut mem, &text, &mut output, "0K", 0).unwrap();
+    out.select(&mut mem, 3, Some(&0x40)).unwrap();
+    out.write(&mut mem, &text, &mut output, "K", 0).unwrap();
+    out.select(&mut mem, 0xfffd, None).unwrap();
+    out.graphics(false);
+    out.write(&mut mem, &text, &mut output, "0K", 0).unwrap();
+
+    assert_eq!(&output.0, "┐█0K");
+    assert_eq!(&output.1, "┐█0K");
+    assert_eq!(mem.loadb(0x42).unwrap(), 0x4b);
+}
+
+#[test]
+fn test_echo() {
+    let data = mem::default();
+    let mut mem = mem::new(data).unwrap();
+    let header = header::init_test(&mut mem);
+    let text = text::init(&mem, &header).unwrap();
+    let mut out = init();
+    let mut output = Out(String::new(), String::new(), String::new());
+
+    assert!(!out.printed());
+    out.echo(&mem, &mut output, "læ");
+    assert!(!out.printed());
+    out.write(&mut mem, &text, &mut output, "A", 0).unwrap();
+    assert!(out.printed());
+    assert!(!out.printed());
+    out.select(&mut mem, 0xffff, None).unwrap();
+    out.echo(&mem, &mut output, "B");
+    out.write(&mut mem, &text, &mut output, "C", 0).unwrap();
+    assert!(!out.printed());
+
+    assert_eq!(&output.0, "laeA");
+}
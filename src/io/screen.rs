@@ -10,6 +10,7 @@ use crate::{
 pub struct Screen {
     v: u8,
     font: u16,
+    graphics: bool,
     width: usize,
 }
 
@@ -69,7 +70,7 @@ impl Screen {
         if font == 0 {
             return self.font;
         }
-        if interface.window_font(font) {
+        if (font == 3 && self.graphics) || interface.window_font(font) {
             let old = self.font;
             self.font = font;
             return old;
@@ -77,6 +78,20 @@ impl Screen {
         0
     }
 
+    /// Is the text drawn by the interpreter with the character graphics font ?
+    pub fn graphics(&self) -> bool {
+        self.graphics && self.font == 3
+    }
+
+    /// Tell the interface to clear the screen and go back to its initial windows, style, buffering, font and colors
+    pub fn reset<I: Interface>(&mut self, interface: &mut I) -> Result<(), Error> {
+        interface.window_erase(0xffff);
+        interface.window_style(0);
+        interface.window_buffer(1);
+        self.font(interface, 1);
+        self.color(interface, 1, 1)
+    }
+
     pub fn color<I: Interface>(
         &self,
         interface: &mut I,
@@ -93,6 +108,11 @@ impl Screen {
         interface.window_color(foreground, background);
     }
 
+    /// Set the `width` of the screen used by the status line
+    pub fn resize(&mut self, width: u16) {
+        self.width = width as usize;
+    }
+
     pub fn style<I: Interface>(&self, interface: &mut I, style: u16) {
         interface.window_style(style);
     }
@@ -146,6 +166,7 @@ pub fn init(mem: &Mem, config: &Config) -> Screen {
     Screen {
         v: mem[0],
         font: 1,
+        graphics: config.graphics,
         width: config.screen.0 as usize,
     }
 }
@@ -191,6 +212,13 @@ fn test() {
     assert_eq!(screen.font(&mut output, 0), 4);
     assert_eq!(screen.font(&mut output, 3), 0);
     assert_eq!(screen.font(&mut output, 1), 4);
+    assert!(!screen.graphics());
+
+    screen.graphics = true;
+    assert_eq!(screen.font(&mut output, 3), 1);
+    assert!(screen.graphics());
+    assert_eq!(screen.font(&mut output, 1), 3);
+    assert!(!screen.graphics());
 
     state.set_var(&mut mem, 0x10, 1).unwrap();
     screen.width = 4;
@@ -1,8 +1,16 @@
-use crate::interface::Interface;
+use crate::interface::{Config, Interface};
 
-// other sounds than bleeps unsuported
-pub fn bleep<I: Interface>(interface: &mut I, sound: u16) {
+/// Emit a bleep, or give the other sound effects to the interface if it can play them
+pub fn effect<I: Interface>(
+    interface: &mut I,
+    config: &Config,
+    sound: u16,
+    effect: u16,
+    volume: u16,
+) {
     if sound < 3 {
         interface.bleep(sound);
+    } else if config.sound {
+        interface.sound(sound, effect, volume & 0xff);
     }
 }
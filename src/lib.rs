@@ -2,6 +2,8 @@
 //!
 //! The entry point is the `main` function.
 //! Take a look at the binary part of this crate to see an example of how to use.
+//! Games can also be played headlessly from a walkthrough with the [`replay`] function.
+//! From C, build the library as a `cdylib` and use the header `include/rzif.h`.
 //! This interpreter follow [The Z-Machine Standards Document](http://inform-fiction.org/zmachine/standards/z1point1/index.html) version 1.1.
 
 #![allow(clippy::too_many_arguments)]
@@ -30,27 +32,69 @@ mod tables {
     pub mod header;
     pub mod obj;
     pub mod opcode;
+    pub mod patch;
 }
 
 use self::other::*;
 mod other {
+    pub mod blorb;
     pub mod err;
+    pub mod extract;
+    pub mod ffi;
+    pub mod harness;
+    pub mod info;
     pub mod interface;
     pub mod machine;
     pub mod quetzal;
+    pub mod validate;
 }
 
 pub mod doc;
+pub use self::extract::*;
+pub use self::harness::*;
+pub use self::info::*;
 pub use self::interface::*;
+pub use self::mem::Story;
+pub use self::patch::{parse_patches, Patch};
+pub use self::validate::validate;
+
+/// How a game ended, returned by [`main`]
+#[derive(Clone, Debug)]
+pub enum Outcome {
+    /// The game quit with `@quit`
+    Quit,
+    /// The interface stopped the game, see [`Interface::running`]
+    Stopped,
+    /// The game couldn't go on because of this error
+    Fatal(Error),
+}
+
+impl From<Error> for Outcome {
+    fn from(error: Error) -> Outcome {
+        match error.cause {
+            Cause::Quit => Outcome::Quit,
+            Cause::Stopped => Outcome::Stopped,
+            _ => Outcome::Fatal(error),
+        }
+    }
+}
+
+/// The storyfile contained in a `file`, which can be a Blorb file or the storyfile itself
+///
+/// Games given clones of the same [`Story`] share its static and high memory, and only copy their dynamic memory: to play a Blorb file many times, extract its storyfile once.
+pub fn storyfile(file: impl Into<Story>) -> Result<Story, Error> {
+    blorb::story(file)
+}
 
 /// Starts the z-machine interpreter
 ///
-/// The first argument is the contents of the storyfile to play.
+/// The first argument is the contents of the storyfile to play, or of a Blorb file containing it. It can be shared by many games, see [`storyfile`].
 /// The second is the configuration of your inferface.
 /// The third is the callback functions this crate use to interact with your interface.
 /// Before calling this function, the screen must be prepared as described [here](crate::doc#starting-a-game).\
-/// This function returns at the end of the game
-pub fn main<I: Interface>(file: Vec<u8>, config: Config, mut interface: I) {
-    let error = machine::init(file, config, &mut interface).unwrap_err();
-    interface.error(error);
+/// This function returns at the end of the game, with how it ended: the interface is given back to offer to play again, restore or quit.
+pub fn main<I: Interface>(file: impl Into<Story>, config: Config, interface: &mut I) -> Outcome {
+    machine::init(file.into(), config, interface)
+        .unwrap_err()
+        .into()
 }
@@ -1,5 +1,10 @@
-use std::{env, fs, thread, time};
+use std::{env, fs, process, thread, time};
+mod extract;
+mod remglk;
+mod replay;
+mod serve;
 mod utils;
+mod validate;
 
 fn main() {
     let mut args = env::args();
@@ -7,10 +12,51 @@ fn main() {
     let path = match args.next() {
         Some(path) => path,
         None => {
-            eprintln!("Usage: {} path/to/storyfile [never|once|always|quit]", name);
+            eprintln!(
+                "Usage: {} path/to/storyfile [never|once|always|quit|pedantic|forgiving|frotz] [seed]",
+                name
+            );
+            eprintln!(
+                "       {} replay path/to/storyfile path/to/walkthrough seed [path/to/golden]",
+                name
+            );
+            eprintln!("       {} validate path/to/storyfile", name);
+            eprintln!("       {} extract path/to/storyfile [text|json]", name);
+            eprintln!(
+                "       {} remglk path/to/storyfile [never|once|always|quit|pedantic|forgiving|frotz] [seed]",
+                name
+            );
+            eprintln!(
+                "       {} serve path/to/storyfile address:port [path/to/saves] [idle seconds] [max sessions]",
+                name
+            );
+            eprintln!(
+                "The saves of a server are kept by the names of the players, without passwords"
+            );
+            eprintln!("Compatibility patches are also read from the file named by RZIF_PATCHES");
             return;
         }
     };
+    if path == "replay" {
+        replay::main(&name, args);
+        return;
+    }
+    if path == "validate" {
+        validate::main(&name, args);
+        return;
+    }
+    if path == "extract" {
+        extract::main(&name, args);
+        return;
+    }
+    if path == "remglk" {
+        remglk::main(&name, args);
+        return;
+    }
+    if path == "serve" {
+        serve::main(&name, args);
+        return;
+    }
     let story = match fs::read(path) {
         Ok(story) => story,
         Err(err) => {
@@ -19,6 +65,20 @@ fn main() {
         }
     };
     let level = args.next().unwrap_or_else(|| "always".to_string());
+    // an invalid seed is ignored, the generator is seeded from the clock
+    let seed = args.next().and_then(|seed| match seed.parse() {
+        Ok(seed) => Some(seed),
+        Err(err) => {
+            eprintln!("invalid seed {}: {}", seed, err);
+            thread::sleep(time::Duration::from_secs(2));
+            None
+        }
+    });
+    let patches = patches().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        thread::sleep(time::Duration::from_secs(2));
+        Vec::new()
+    });
 
     let config = rzif::Config {
         status: true,
@@ -33,22 +93,62 @@ fn main() {
         default_color: (2, 9),
         true_color: (0x0000, 0x7fff),
         picture: false,
-        error: match level.as_str() {
-            "never" => rzif::ErrorLevel::Never,
-            "once" => rzif::ErrorLevel::Once,
-            "always" => rzif::ErrorLevel::Always,
-            "quit" => rzif::ErrorLevel::Quit,
-            _ => {
-                eprintln!("invalid parameter: {}", level);
-                thread::sleep(time::Duration::from_secs(2));
-                rzif::ErrorLevel::Always
-            }
-        },
+        graphics: true,
+        undo: true,
+        sound: false,
+        transparency: false,
+        patches: &patches,
+        error: policy(&level).unwrap_or_else(|| {
+            eprintln!("invalid parameter: {}", level);
+            thread::sleep(time::Duration::from_secs(2));
+            rzif::ErrorPolicy::new(rzif::ErrorLevel::Always)
+        }),
+        seed,
+        generator: rzif::Generator::Xorshift,
     };
 
-    let io = utils::init(story[0], config.screen.0, config.screen.1);
+    let mut io = utils::init(story[0], config.screen.0, config.screen.1);
+
+    let outcome = rzif::main(story, config, &mut io);
+    io.flush();
+    if let rzif::Outcome::Fatal(error) = outcome {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+/// The error policy named `level` on the command line
+fn policy(level: &str) -> Option<rzif::ErrorPolicy> {
+    Some(match level {
+        "never" => rzif::ErrorPolicy::new(rzif::ErrorLevel::Never),
+        "once" => rzif::ErrorPolicy::new(rzif::ErrorLevel::Once),
+        "always" => rzif::ErrorPolicy::new(rzif::ErrorLevel::Always),
+        "quit" => rzif::ErrorPolicy::new(rzif::ErrorLevel::Quit),
+        "pedantic" => rzif::ErrorPolicy::pedantic(),
+        "forgiving" => rzif::ErrorPolicy::forgiving(),
+        "frotz" => rzif::ErrorPolicy::frotz(),
+        _ => return None,
+    })
+}
+
+/// The compatibility patches of the file named by RZIF_PATCHES, if any
+fn patches() -> Result<Vec<rzif::Patch>, String> {
+    let path = match env::var("RZIF_PATCHES") {
+        Ok(path) => path,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("Error opening the patches {}: {}", path, err))?;
+    rzif::parse_patches(&text).map_err(|err| format!("Error in the patches {}: {}", path, err))
+}
 
-    rzif::main(story, config, io);
+/// A file name that stays in its directory, without separators or leading dots
+fn file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .filter(|&char| char.is_alphanumeric() || matches!(char, '-' | '_' | '.'))
+        .collect::<String>();
+    name.trim_start_matches('.').to_string()
 }
 
 fn get_size() -> (u16, u16) {
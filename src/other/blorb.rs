@@ -0,0 +1,80 @@
+use crate::{err::*, mem::Story};
+
+/// Is this file a Blorb file ?
+pub fn is_blorb(file: &[u8]) -> bool {
+    file.len() >= 12 && &file[0..4] == b"FORM" && &file[8..12] == b"IFRS"
+}
+
+/// The first chunk of this type in a Blorb file
+pub fn chunk<'a>(file: &'a [u8], id: &[u8; 4]) -> Option<&'a [u8]> {
+    let end = (8 + get(file, 4)? as usize).min(file.len());
+    let mut addr = 12;
+    while addr + 8 <= end {
+        let len = get(file, addr + 4)? as usize;
+        let data = file.get(addr + 8..addr + 8 + len)?;
+        if &file[addr..addr + 4] == id {
+            return Some(data);
+        }
+        addr += 8 + len + len % 2;
+    }
+    None
+}
+
+/// The storyfile contained in a file, which can be a Blorb file or the storyfile itself
+pub fn story(file: impl Into<Story>) -> Result<Story, Error> {
+    let file = file.into();
+    if !is_blorb(&file) {
+        return Ok(file);
+    }
+    match chunk(&file, b"ZCOD") {
+        Some(story) => Ok(story.into()),
+        None => error(Cause::NoStory, (0, 0)),
+    }
+}
+
+fn get(file: &[u8], addr: usize) -> Option<u32> {
+    let bytes = file.get(addr..addr + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+pub fn blorb(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let mut data = b"IFRS".to_vec();
+    for (id, chunk) in chunks {
+        data.extend(*id);
+        data.extend(&(chunk.len() as u32).to_be_bytes());
+        data.extend(*chunk);
+        if chunk.len() % 2 == 1 {
+            data.push(0);
+        }
+    }
+    let mut file = b"FORM".to_vec();
+    file.extend(&(data.len() as u32).to_be_bytes());
+    file.extend(data);
+    file
+}
+
+#[test]
+fn test_story() {
+    let file = blorb(&[(b"RIdx", &[0; 4]), (b"IFmd", b"abc"), (b"ZCOD", b"story")]);
+    assert!(is_blorb(&file));
+    assert_eq!(chunk(&file, b"IFmd"), Some(&b"abc"[..]));
+    assert_eq!(chunk(&file, b"Exec"), None);
+    assert_eq!(&story(file).unwrap()[..], b"story");
+    let file = Story::from(b"story".to_vec());
+    assert_eq!(story(file.clone()).unwrap().as_ptr(), file.as_ptr());
+
+    let mut file = blorb(&[(b"IFmd", b"abc")]);
+    assert_eq!(story(file.clone()).unwrap_err().cause, Cause::NoStory);
+    file.truncate(file.len() - 2);
+    assert_eq!(chunk(&file, b"IFmd"), None);
+}
+
+#[test]
+fn test_play() {
+    // print_num 7, quit
+    let story = crate::machine::story(&[0xe6, 0x7f, 0x07, 0xba]);
+    let file = blorb(&[(b"IFmd", b"abc"), (b"ZCOD", &story)]);
+    let (output, err) = crate::machine::play(file, "");
+    assert_eq!((output.as_str(), err.cause), ("7", Cause::Quit));
+}
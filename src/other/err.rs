@@ -1,14 +1,42 @@
+use std::fmt;
+
 #[derive(Clone, Debug)]
 /// Information about the error that occured
+///
+/// Its [`Display`](fmt::Display) form is a multi-line report, suitable for bug reports.
 pub struct Error {
     /// the cause of the error
     pub cause: Cause,
-    /// some information about the error
+    /// some information about the error (see [`details`](Error::details) for a structured form)
     pub data: (u16, u16),
     /// backtrace
     pub trace: Vec<Trace>,
     /// is this error fatal ?
     pub fatal: bool,
+    /// the instruction that was executed (`None` if it couldn't be decoded)
+    pub instr: Option<Instruction>,
+    /// the routines that were executed, from the innermost (empty in the main routine)
+    pub calls: Vec<Call>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A decoded instruction
+pub struct Instruction {
+    /// its address
+    pub addr: usize,
+    /// the name of its opcode, as in the standard
+    pub name: &'static str,
+    /// the values of its operands
+    pub operands: Vec<u16>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A routine in the call stack
+pub struct Call {
+    /// the address of the routine (`None` if it was called before a restore)
+    pub routine: Option<usize>,
+    /// the values of its local variables
+    pub locals: Vec<u16>,
 }
 
 pub fn err(cause: Cause, data: (u16, u16)) -> Error {
@@ -17,17 +45,14 @@ pub fn err(cause: Cause, data: (u16, u16)) -> Error {
         data,
         trace: Vec::new(),
         fatal: false,
+        instr: None,
+        calls: Vec::new(),
     }
 }
 
 pub fn error<T>(cause: Cause, data: (u16, u16)) -> Result<T, Error> {
     // or Result<!, Error>
-    Err(Error {
-        cause,
-        data,
-        trace: Vec::new(),
-        fatal: false,
-    })
+    Err(err(cause, data))
 }
 
 pub fn fatal<T>(mut result: Result<T, Error>) -> Result<T, Error> {
@@ -44,6 +69,111 @@ pub fn trace<T>(mut result: Result<T, Error>, trace: Trace) -> Result<T, Error>
     result
 }
 
+impl Error {
+    /// The cause of the error, with its data
+    pub fn details(&self) -> Details {
+        let (a, b) = self.data;
+        match self.cause {
+            Cause::Quit => Details::Quit,
+            Cause::Stopped => Details::Stopped,
+            Cause::BadVer => Details::BadVer { version: a },
+            Cause::TooShort => Details::TooShort { len: a },
+            Cause::StaticOut => Details::StaticOut { addr: a },
+            Cause::NoStory => Details::NoStory,
+            Cause::BadChecksum => Details::BadChecksum {
+                expected: a,
+                found: b,
+            },
+            Cause::BadPatch => Details::BadPatch { line: a },
+            Cause::WriteOut => Details::WriteOut { addr: a },
+            Cause::ReadOut => Details::ReadOut { addr: a },
+            Cause::DivByZero => Details::DivByZero,
+            Cause::MissingOperand => Details::MissingOperand {
+                expected: a,
+                found: b,
+            },
+            Cause::BadOpcode => Details::BadOpcode { form: a, opcode: b },
+            Cause::StackUnderflow => Details::StackUnderflow,
+            Cause::NoVar => Details::NoVar { var: a },
+            Cause::NoLocal => Details::NoLocal { local: a },
+            Cause::NoLocalInMain => Details::NoLocalInMain,
+            Cause::MainReturned => Details::MainReturned { value: a },
+            Cause::PcOut => Details::PcOut,
+            Cause::TooManyLocals => Details::TooManyLocals { count: a },
+            Cause::TextBufferOut => Details::TextBufferOut { addr: a },
+            Cause::TerminatingOut => Details::TerminatingOut { addr: a },
+            Cause::StrOut => Details::StrOut,
+            Cause::AlphabetOut => Details::AlphabetOut { addr: a },
+            Cause::UnicodeOut => Details::UnicodeOut { addr: a },
+            Cause::AbbrOut => Details::AbbrOut { addr: a },
+            Cause::DictOut => Details::DictOut { addr: a },
+            Cause::ObjectsOut => Details::ObjectsOut { addr: a },
+            Cause::PropOut => Details::PropOut { obj: a },
+            Cause::BadUnicodeChar => Details::BadUnicodeChar { char: a },
+            Cause::BadZSCIIChar => Details::BadZSCIIChar { zscii: a },
+            Cause::NestedAbbr => Details::NestedAbbr,
+            Cause::AbbrIncompleteZSCII => Details::AbbrIncompleteZSCII,
+            Cause::BadObj => Details::BadObj { obj: a },
+            Cause::NotChildOfParent => Details::NotChildOfParent {
+                child: a,
+                parent: b,
+            },
+            Cause::BadAttr => Details::BadAttr { attr: a },
+            Cause::BadProp => Details::BadProp { prop: a },
+            Cause::GetLongProp => Details::GetLongProp { obj: a, prop: b },
+            Cause::PutLongProp => Details::PutLongProp { obj: a, prop: b },
+            Cause::NoProp => Details::NoProp { obj: a, prop: b },
+            Cause::BadInputStream => Details::BadInputStream { stream: a },
+            Cause::BadOutputStream => Details::BadOutputStream { stream: a },
+            Cause::NoOutputS3 => Details::NoOutputS3,
+            Cause::OutputS3Overflow => Details::OutputS3Overflow,
+            Cause::BadColor => Details::BadColor { color: a },
+            Cause::SaveInterrupt => Details::SaveInterrupt,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.fatal {
+            write!(f, "Fatal error: {}", self.details())?;
+        } else {
+            write!(f, "Error: {}", self.details())?;
+        }
+
+        if self.trace.is_empty() {
+            return write!(f, "\n  while initializing the interpreter");
+        }
+        for frame in self.trace.iter().rev() {
+            write!(f, "\n  while {}", frame)?;
+        }
+        if let Some(ref instr) = self.instr {
+            write!(f, "\n  instruction: {}", instr.name)?;
+            for operand in instr.operands.iter() {
+                write!(f, " ${:04x}", operand)?;
+            }
+        }
+        for call in self.calls.iter() {
+            match call.routine {
+                Some(routine) => write!(f, "\n  in routine ${:x}", routine)?,
+                None => write!(f, "\n  in a routine called before the last restore")?,
+            }
+            if !call.locals.is_empty() {
+                write!(f, ", locals:")?;
+                for local in call.locals.iter() {
+                    write!(f, " ${:04x}", local)?;
+                }
+            }
+        }
+        if self.instr.is_some() {
+            write!(f, "\n  in the main routine")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
 #[derive(Clone, Copy, Debug)]
 /// A frame of the backtrace
 pub enum Trace {
@@ -59,117 +189,539 @@ pub enum Trace {
     Rout(usize),
 }
 
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (text, addr) = match *self {
+            Trace::Decode(addr) => ("decoding instruction", addr),
+            Trace::Exec(addr) => ("executing instruction", addr),
+            Trace::String(addr) => ("decoding string", addr),
+            Trace::Abbr(addr) => ("decoding abbreviation", addr),
+            Trace::Rout(addr) => ("calling routine", addr),
+        };
+        write!(f, "{} at ${:x}", text, addr)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 /// The cause of the error that occured
+///
+/// See [`Details`] for the cause with its data.
+/// The values of the causes don't change, new ones are added at the end: they are given to C as the `RZIF_CAUSE_*` of `include/rzif.h`.
 pub enum Cause {
     /// The game has quit
-    Quit,
+    Quit = 0,
     /// Storyfile was not in a supported version\
     /// data: the version of this storyfile
-    BadVer,
+    BadVer = 1,
     /// Storyfile too short (it must be at least $40 bytes long to hold the header)\
     /// data: length of the storyfile
-    TooShort,
+    TooShort = 2,
     /// End of static memory out of bounds\
     /// data: end of the static memory
-    StaticOut,
+    StaticOut = 3,
     /// Tried to write outside of dynamic memory\
     /// data: address writen
-    WriteOut,
+    WriteOut = 4,
     /// Tried to read out of bounds\
     /// data: address readed
-    ReadOut,
+    ReadOut = 5,
     /// Tried to divide by zero
-    DivByZero,
+    DivByZero = 6,
     /// Not enough operands given
     /// data: minimum nuber of operands, current number of operands
-    MissingOperand,
+    MissingOperand = 7,
     /// This opcode is invalid\
     /// data: operand count, opcode number
-    BadOpcode,
+    BadOpcode = 8,
     /// The stack underflowed
-    StackUnderflow,
+    StackUnderflow = 9,
     /// Invalid variable id\
     /// data: variable id
-    NoVar,
+    NoVar = 10,
     /// Non-existent local variable\
     /// data: variable id
-    NoLocal,
+    NoLocal = 11,
     /// Access to a local variable from main routine
-    NoLocalInMain,
+    NoLocalInMain = 12,
     /// Main routine returned
-    MainReturned,
+    MainReturned = 13,
     /// Program counter out of bounds
-    PcOut,
+    PcOut = 14,
     /// Routine called that have more than 15 local variables\
     /// data: number of local variables
-    TooManyLocals,
+    TooManyLocals = 15,
     /// Text buffer out of bounds\
     /// data: address of the text buffer
-    TextBufferOut,
+    TextBufferOut = 16,
     /// Terminating charachters table out of bounds\
     /// data: address of the table
-    TerminatingOut,
+    TerminatingOut = 17,
     /// Part of string out of bounds\
     /// The address of the string will be in the backtrace
-    StrOut,
+    StrOut = 18,
     /// Alphabet table out of bounds\
     /// data: address of the table
-    AlphabetOut,
+    AlphabetOut = 19,
     /// Unicode translation table out of bounds\
     /// data: address of the table
-    UnicodeOut,
+    UnicodeOut = 20,
     /// Invalid unicode char was encontered\
     /// data: unicode char
-    BadUnicodeChar,
+    BadUnicodeChar = 21,
     /// Invalid ZSCII char was encontered\
     /// data: ZSCII char
-    BadZSCIIChar,
+    BadZSCIIChar = 22,
     /// Abbreviation containing another one\
     /// The addresses of the abbreviations will be in the backtrace
-    NestedAbbr,
+    NestedAbbr = 23,
     /// Abbreviation ending with an incomplete char\
     /// The address of the abbreviation will be in the backtrace
-    AbbrIncompleteZSCII,
+    AbbrIncompleteZSCII = 24,
     /// Invalid object\
     /// data: object number
-    BadObj,
+    BadObj = 25,
     /// Object not child of his parent\
     /// data: number of the child and his parent
-    NotChildOfParent,
+    NotChildOfParent = 26,
     /// Invalid attribute\
     /// data: attribute number
-    BadAttr,
+    BadAttr = 27,
     /// Invalid property\
     /// data: property number
-    BadProp,
+    BadProp = 28,
     /// Got property larger than two bytes\
     /// data: object, property
-    GetLongProp,
+    GetLongProp = 29,
     /// Set property larger than two bytes\
     /// data: object, property
-    PutLongProp,
+    PutLongProp = 30,
     /// Use of a non-existent property\
     /// data: object, property
-    NoProp,
+    NoProp = 31,
     /// Invalid output stream\
     /// data: stream number
-    BadInputStream,
+    BadInputStream = 32,
     /// Invalid input stream\
     /// data: stream number
-    BadOutputStream,
+    BadOutputStream = 33,
     /// Output stream 3 disabled while not enabled
-    NoOutputS3,
+    NoOutputS3 = 34,
     /// output stream 3 enabled for the 17th
-    OutputS3Overflow,
+    OutputS3Overflow = 35,
     /// Invalid color\
     /// data: color
-    BadColor,
+    BadColor = 36,
     /// Save during interupt\
+    SaveInterrupt = 37,
+    /// The interface stopped the game (see [`Interface::running`](crate::Interface::running))
+    Stopped = 38,
+    /// Blorb file without a storyfile
+    NoStory = 39,
+    /// The checksum of the storyfile is wrong (see [`validate`](crate::validate))\
+    /// data: checksum in the header, checksum of the storyfile
+    BadChecksum = 40,
+    /// Abbreviations table out of bounds\
+    /// data: address of the table
+    AbbrOut = 41,
+    /// Dictionary out of bounds\
+    /// data: address of the dictionary
+    DictOut = 42,
+    /// Object table out of bounds\
+    /// data: address of the table
+    ObjectsOut = 43,
+    /// Property table out of bounds\
+    /// data: object number
+    PropOut = 44,
+    /// Invalid line in a file of [compatibility patches](crate::parse_patches)\
+    /// data: number of the line
+    BadPatch = 45,
+}
+
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Cause::Quit => "the game has quit",
+            Cause::Stopped => "the game was stopped",
+            Cause::BadVer => "unsupported version",
+            Cause::TooShort => "storyfile too short",
+            Cause::StaticOut => "static memory out of bounds",
+            Cause::NoStory => "no storyfile in the blorb file",
+            Cause::BadChecksum => "wrong checksum",
+            Cause::BadPatch => "invalid patch",
+            Cause::WriteOut => "write outside of dynamic memory",
+            Cause::ReadOut => "read out of bounds",
+            Cause::DivByZero => "division by zero",
+            Cause::MissingOperand => "missing operand",
+            Cause::BadOpcode => "invalid opcode",
+            Cause::StackUnderflow => "stack underflow",
+            Cause::NoVar => "invalid variable",
+            Cause::NoLocal => "non-existent local variable",
+            Cause::NoLocalInMain => "local variable in the main routine",
+            Cause::MainReturned => "the main routine returned",
+            Cause::PcOut => "program counter out of bounds",
+            Cause::TooManyLocals => "too many local variables",
+            Cause::TextBufferOut => "text buffer out of bounds",
+            Cause::TerminatingOut => "terminating characters table out of bounds",
+            Cause::StrOut => "string out of bounds",
+            Cause::AlphabetOut => "alphabet table out of bounds",
+            Cause::UnicodeOut => "unicode translation table out of bounds",
+            Cause::AbbrOut => "abbreviations table out of bounds",
+            Cause::DictOut => "dictionary out of bounds",
+            Cause::ObjectsOut => "object table out of bounds",
+            Cause::PropOut => "property table out of bounds",
+            Cause::BadUnicodeChar => "invalid unicode char",
+            Cause::BadZSCIIChar => "invalid ZSCII char",
+            Cause::NestedAbbr => "nested abbreviation",
+            Cause::AbbrIncompleteZSCII => "abbreviation ending with an incomplete char",
+            Cause::BadObj => "invalid object",
+            Cause::NotChildOfParent => "object not child of its parent",
+            Cause::BadAttr => "invalid attribute",
+            Cause::BadProp => "invalid property",
+            Cause::GetLongProp => "got a property longer than two bytes",
+            Cause::PutLongProp => "put a property longer than two bytes",
+            Cause::NoProp => "non-existent property",
+            Cause::BadInputStream => "invalid input stream",
+            Cause::BadOutputStream => "invalid output stream",
+            Cause::NoOutputS3 => "output stream 3 disabled while not enabled",
+            Cause::OutputS3Overflow => "output stream 3 enabled too many times",
+            Cause::BadColor => "invalid color",
+            Cause::SaveInterrupt => "save during an interrupt routine",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The cause of an error, with its data (see [`Error::details`])
+///
+/// The variants are the same as in [`Cause`].
+pub enum Details {
+    /// The game has quit
+    Quit,
+    /// Storyfile was not in a supported version
+    BadVer {
+        /// The version of the storyfile
+        version: u16,
+    },
+    /// Storyfile too short (it must be at least $40 bytes long to hold the header)
+    TooShort {
+        /// The length of the storyfile
+        len: u16,
+    },
+    /// End of static memory out of bounds
+    StaticOut {
+        /// The end of the static memory
+        addr: u16,
+    },
+    /// Tried to write outside of dynamic memory
+    WriteOut {
+        /// The address written
+        addr: u16,
+    },
+    /// Tried to read out of bounds
+    ReadOut {
+        /// The address read
+        addr: u16,
+    },
+    /// Tried to divide by zero
+    DivByZero,
+    /// Not enough operands given
+    MissingOperand {
+        /// The minimum number of operands
+        expected: u16,
+        /// The number of operands given
+        found: u16,
+    },
+    /// This opcode is invalid
+    BadOpcode {
+        /// 0 for 0OP, 1 for 1OP, 2 for 2OP, 3 for VAR and 4 for EXT
+        form: u16,
+        /// The number of the opcode
+        opcode: u16,
+    },
+    /// The stack underflowed
+    StackUnderflow,
+    /// Invalid variable id
+    NoVar {
+        /// The variable id
+        var: u16,
+    },
+    /// Non-existent local variable
+    NoLocal {
+        /// The number of the local variable, starting at 0
+        local: u16,
+    },
+    /// Access to a local variable from main routine
+    NoLocalInMain,
+    /// Main routine returned
+    MainReturned {
+        /// The value returned by an interrupt routine
+        value: u16,
+    },
+    /// Program counter out of bounds
+    PcOut,
+    /// Routine called that have more than 15 local variables
+    TooManyLocals {
+        /// The number of local variables
+        count: u16,
+    },
+    /// Text buffer out of bounds
+    TextBufferOut {
+        /// The address of the text buffer
+        addr: u16,
+    },
+    /// Terminating charachters table out of bounds
+    TerminatingOut {
+        /// The address of the table
+        addr: u16,
+    },
+    /// Part of string out of bounds
+    StrOut,
+    /// Alphabet table out of bounds
+    AlphabetOut {
+        /// The address of the table
+        addr: u16,
+    },
+    /// Unicode translation table out of bounds
+    UnicodeOut {
+        /// The address of the table
+        addr: u16,
+    },
+    /// Invalid unicode char was encontered
+    BadUnicodeChar {
+        /// The unicode char
+        char: u16,
+    },
+    /// Invalid ZSCII char was encontered
+    BadZSCIIChar {
+        /// The ZSCII char
+        zscii: u16,
+    },
+    /// Abbreviation containing another one
+    NestedAbbr,
+    /// Abbreviation ending with an incomplete char
+    AbbrIncompleteZSCII,
+    /// Invalid object
+    BadObj {
+        /// The object number
+        obj: u16,
+    },
+    /// Object not child of his parent
+    NotChildOfParent {
+        /// The number of the child
+        child: u16,
+        /// The number of its parent
+        parent: u16,
+    },
+    /// Invalid attribute
+    BadAttr {
+        /// The attribute number
+        attr: u16,
+    },
+    /// Invalid property
+    BadProp {
+        /// The property number
+        prop: u16,
+    },
+    /// Got property larger than two bytes
+    GetLongProp {
+        /// The object number
+        obj: u16,
+        /// The property number
+        prop: u16,
+    },
+    /// Set property larger than two bytes
+    PutLongProp {
+        /// The object number
+        obj: u16,
+        /// The property number
+        prop: u16,
+    },
+    /// Use of a non-existent property
+    NoProp {
+        /// The object number
+        obj: u16,
+        /// The property number
+        prop: u16,
+    },
+    /// Invalid output stream
+    BadInputStream {
+        /// The stream number
+        stream: u16,
+    },
+    /// Invalid input stream
+    BadOutputStream {
+        /// The stream number
+        stream: u16,
+    },
+    /// Output stream 3 disabled while not enabled
+    NoOutputS3,
+    /// output stream 3 enabled for the 17th
+    OutputS3Overflow,
+    /// Invalid color
+    BadColor {
+        /// The color
+        color: u16,
+    },
+    /// Save during interupt
     SaveInterrupt,
+    /// The interface stopped the game (see [`Interface::running`](crate::Interface::running))
+    Stopped,
+    /// Blorb file without a storyfile
+    NoStory,
+    /// The checksum of the storyfile is wrong (see [`validate`](crate::validate))
+    BadChecksum {
+        /// The checksum in the header
+        expected: u16,
+        /// The checksum of the storyfile
+        found: u16,
+    },
+    /// Abbreviations table out of bounds
+    AbbrOut {
+        /// The address of the table
+        addr: u16,
+    },
+    /// Dictionary out of bounds
+    DictOut {
+        /// The address of the dictionary
+        addr: u16,
+    },
+    /// Object table out of bounds
+    ObjectsOut {
+        /// The address of the table
+        addr: u16,
+    },
+    /// Property table out of bounds
+    PropOut {
+        /// The object number
+        obj: u16,
+    },
+    /// Invalid line in a file of [compatibility patches](crate::parse_patches)
+    BadPatch {
+        /// The number of the line
+        line: u16,
+    },
 }
 
-pub const CAUSE_COUNT: usize = Cause::SaveInterrupt as usize + 1;
+impl fmt::Display for Details {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Details::BadVer { version } => write!(f, "version {} is not supported", version),
+            Details::TooShort { len } => write!(
+                f,
+                "the storyfile is too short to hold the header ({} bytes)",
+                len
+            ),
+            Details::StaticOut { addr } => out(f, "static memory", addr),
+            Details::WriteOut { addr } => write!(
+                f,
+                "tried to write outside of dynamic memory at ${:04x}",
+                addr
+            ),
+            Details::ReadOut { addr } => write!(f, "tried to read out of bounds at ${:04x}", addr),
+            Details::MissingOperand { expected, found } => {
+                write!(f, "expected {} operands, found only {}", expected, found)
+            }
+            Details::BadOpcode { form, opcode } => write!(
+                f,
+                "opcode {}:${:02x} doesn't exist",
+                match form {
+                    0 => "0OP",
+                    1 => "1OP",
+                    2 => "2OP",
+                    3 => "VAR",
+                    _ => "EXT",
+                },
+                opcode
+            ),
+            Details::NoVar { var } => invalid(f, "variable", var),
+            Details::NoLocal { local } => {
+                write!(f, "local variable {} doesn't exist", local + 1)
+            }
+            Details::TooManyLocals { count } => write!(
+                f,
+                "a routine can't have more than 15 local variables (found {})",
+                count
+            ),
+            Details::TextBufferOut { addr } => out(f, "text buffer", addr),
+            Details::TerminatingOut { addr } => out(f, "terminating characters table", addr),
+            Details::AlphabetOut { addr } => out(f, "alphabet table", addr),
+            Details::UnicodeOut { addr } => out(f, "unicode translation table", addr),
+            Details::AbbrOut { addr } => out(f, "abbreviations table", addr),
+            Details::DictOut { addr } => out(f, "dictionary", addr),
+            Details::ObjectsOut { addr } => out(f, "object table", addr),
+            Details::PropOut { obj } => {
+                write!(
+                    f,
+                    "the property table of the object {} is out of bounds",
+                    obj
+                )
+            }
+            Details::BadChecksum { expected, found } => write!(
+                f,
+                "the checksum of the storyfile is ${:04x} instead of ${:04x}",
+                found, expected
+            ),
+            Details::BadPatch { line } => write!(f, "the patch on line {} is invalid", line),
+            Details::BadUnicodeChar { char } => {
+                write!(f, "unicode char U+{:04x} is not valid", char)
+            }
+            Details::BadZSCIIChar { zscii } => invalid(f, "ZSCII char", zscii),
+            Details::BadObj { obj } => invalid(f, "object", obj),
+            Details::NotChildOfParent { child, parent } => write!(
+                f,
+                "object {} is not a child of its parent {}",
+                child, parent
+            ),
+            Details::BadAttr { attr } => invalid(f, "attribute", attr),
+            Details::BadProp { prop } => invalid(f, "property", prop),
+            Details::GetLongProp { obj, prop } | Details::PutLongProp { obj, prop } => write!(
+                f,
+                "the property {} of the object {} is longer than two bytes",
+                prop, obj
+            ),
+            Details::NoProp { obj, prop } => write!(
+                f,
+                "the property {} of the object {} doesn't exist",
+                prop, obj
+            ),
+            Details::BadInputStream { stream } => invalid(f, "input stream", stream),
+            Details::BadOutputStream { stream } => invalid(f, "output stream", stream),
+            Details::BadColor { color } => invalid(f, "color", color),
+            Details::Quit => write!(f, "{}", Cause::Quit),
+            Details::Stopped => write!(f, "{}", Cause::Stopped),
+            Details::NoStory => write!(f, "the blorb file doesn't contain a z-code storyfile"),
+            Details::DivByZero => write!(f, "tried to divide by zero"),
+            Details::StackUnderflow => write!(f, "the stack underflowed"),
+            Details::NoLocalInMain => {
+                write!(f, "can't access local variables from the main routine")
+            }
+            Details::MainReturned { .. } => write!(f, "{}", Cause::MainReturned),
+            Details::PcOut => write!(f, "the program counter is out of bounds"),
+            Details::StrOut => write!(f, "tried to print a string that is out of bounds"),
+            Details::NestedAbbr => write!(f, "tried to use an abbreviation in another one"),
+            Details::AbbrIncompleteZSCII => {
+                write!(f, "an abbreviation ended with an incomplete ZSCII char")
+            }
+            Details::NoOutputS3 => {
+                write!(f, "can't disable output stream 3 while it isn't enabled")
+            }
+            Details::OutputS3Overflow => {
+                write!(f, "can't enable output stream 3 more than 16 times")
+            }
+            Details::SaveInterrupt => write!(f, "can't save during an interrupt routine"),
+        }
+    }
+}
+
+fn out(f: &mut fmt::Formatter, text: &str, addr: u16) -> fmt::Result {
+    write!(f, "the {} at ${:04x} is out of bounds", text, addr)
+}
+
+fn invalid(f: &mut fmt::Formatter, text: &str, num: u16) -> fmt::Result {
+    write!(f, "{} {} is not valid", text, num)
+}
+
+pub const CAUSE_COUNT: usize = Cause::BadPatch as usize + 1;
 
 #[derive(Clone, Copy, Debug)]
 /// Why the restore failed
@@ -195,3 +747,77 @@ pub enum SaveError {
     /// This wasn't saved by this game
     GamesDiffer,
 }
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SaveError::BadSave => write!(f, "the save file is malformed"),
+            SaveError::CMemIncomplete => {
+                write!(f, "the compressed memory ends with an incomplete run")
+            }
+            SaveError::CMemLonger(len, max) => write!(
+                f,
+                "the compressed memory expands to {} bytes, more than the {} bytes of dynamic memory",
+                len, max
+            ),
+            SaveError::UMemBadSize(len, size) => write!(
+                f,
+                "the uncompressed memory is {} bytes long instead of {}",
+                len, size
+            ),
+            SaveError::MissingMem => write!(f, "the memory chunk is missing"),
+            SaveError::TwoMem => write!(f, "there are two memory chunks"),
+            SaveError::MissingStks => write!(f, "the stacks chunk is missing"),
+            SaveError::TwoStks => write!(f, "there are two stacks chunks"),
+            SaveError::TwoIFhd => write!(f, "there are two header chunks"),
+            SaveError::GamesDiffer => write!(f, "this file wasn't saved from this game"),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+#[test]
+fn test_display() {
+    let mut error = err(Cause::NoProp, (12, 3));
+    assert_eq!(error.details(), Details::NoProp { obj: 12, prop: 3 });
+    assert_eq!(error.cause.to_string(), "non-existent property");
+    assert_eq!(
+        error.to_string(),
+        "Error: the property 3 of the object 12 doesn't exist\n  while initializing the interpreter"
+    );
+    error.fatal = true;
+    error.trace.push(Trace::String(0x1234));
+    error.trace.push(Trace::Exec(0x42));
+    assert_eq!(
+        error.to_string(),
+        "Fatal error: the property 3 of the object 12 doesn't exist\n  while executing instruction at $42\n  while decoding string at $1234"
+    );
+    assert_eq!(
+        SaveError::UMemBadSize(2, 3).to_string(),
+        "the uncompressed memory is 2 bytes long instead of 3"
+    );
+}
+
+#[test]
+fn test_c_causes() {
+    let causes = include_str!("../../include/rzif.h")
+        .lines()
+        .filter_map(|line| line.strip_prefix("#define RZIF_CAUSE_"))
+        .map(|line| line.split_once(' ').unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(causes.len(), CAUSE_COUNT);
+    for (i, (_, value)) in causes.iter().enumerate() {
+        assert_eq!(value.parse::<usize>().unwrap(), i);
+    }
+    for (cause, name) in [
+        (Cause::Quit, "QUIT"),
+        (Cause::BadChecksum, "BAD_CHECKSUM"),
+        (Cause::BadPatch, "BAD_PATCH"),
+        (Cause::BadZSCIIChar, "BAD_ZSCII_CHAR"),
+        (Cause::BadObj, "BAD_OBJ"),
+        (Cause::SaveInterrupt, "SAVE_INTERRUPT"),
+    ] {
+        assert_eq!(causes[cause as usize].0, name);
+    }
+}
@@ -0,0 +1,204 @@
+//! Extraction of the text of a storyfile
+//!
+//! [`extract`] decodes every text of a storyfile without playing it, to proofread or translate it.
+//! The texts can be written as plain text with their [`Display`](fmt::Display) form, one per line, or as JSON with [`to_json`].
+
+use crate::{err::*, *};
+use std::{collections::HashSet, fmt};
+
+/// A text of a storyfile
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoryText {
+    /// What this text is
+    pub kind: TextKind,
+    /// The address of the encoded text
+    pub addr: usize,
+    /// The decoded text
+    pub text: String,
+}
+
+/// What a [`StoryText`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextKind {
+    /// An abbreviation, with its number
+    Abbreviation(u16),
+    /// The short name of an object, with its number
+    Object(u16),
+    /// A word of the dictionary
+    Word,
+    /// A string inside the code, printed by `print` or `print_ret`
+    Inline,
+    /// A string of high memory
+    String,
+}
+
+/// Decodes the texts of a storyfile
+///
+/// The file can be a storyfile or a Blorb file containing one.
+/// This returns the abbreviations (once per address), the short names of the objects, the words of the dictionary,
+/// and the strings of high memory sorted by address.\
+/// The strings are the ones printed by the code reachable from the start of the game,
+/// and the ones following them up to the end of the storyfile, where compilers put the strings.
+/// Texts that can't be decoded are skipped, [`validate`](crate::validate) reports them.
+pub fn extract(file: &[u8]) -> Result<Vec<StoryText>, Error> {
+    let mem = mem::new(blorb::story(file)?)?;
+    let header = header::read(&mem)?;
+    let text = text::init(&mem, &header)?;
+    let mut texts = Vec::new();
+
+    // unused abbreviations all point to the same string
+    let mut seen = HashSet::new();
+    for abbr in 0..text::abbreviations(&mem) {
+        if let (Ok(addr), Ok(string)) = (text.abbr_addr(&mem, abbr), text.abbr(&mem, abbr)) {
+            if !seen.insert(addr) {
+                continue;
+            }
+            texts.push(StoryText {
+                kind: TextKind::Abbreviation(abbr),
+                addr,
+                text: string,
+            });
+        }
+    }
+    let obj = obj::init(&mem);
+    for n in 1..=obj.count(&mem).unwrap_or(0) as u16 {
+        if let (Ok(addr), Ok(name)) = (obj.name_addr(&mem, n), obj.name(&mem, &text, n)) {
+            if !name.is_empty() {
+                texts.push(StoryText {
+                    kind: TextKind::Object(n),
+                    addr,
+                    text: name,
+                });
+            }
+        }
+    }
+    for addr in dict::init(&mem).words(&mem).unwrap_or_default() {
+        if let Ok((word, _)) = text.decode(&mem, addr) {
+            texts.push(StoryText {
+                kind: TextKind::Word,
+                addr,
+                text: word,
+            });
+        }
+    }
+
+    let code = validate::code(&mem, &text, &mut Vec::new());
+    let mut strings = code.strings;
+    // the strings after the code, each one aligned like a packed address
+    let align = match mem[0x00] {
+        1..=3 => 2,
+        8 => 8,
+        _ => 4,
+    };
+    let start = strings
+        .iter()
+        .filter(|&&(addr, inline, _)| !inline && addr >= code.end)
+        .map(|&(addr, _, _)| addr)
+        .min();
+    if let Some(mut addr) = start {
+        while let Ok((string, end)) = text.decode(&mem, addr) {
+            strings.push((addr, false, string));
+            addr = end.div_ceil(align) * align;
+        }
+    }
+    strings.sort_by_key(|&(addr, _, _)| addr);
+    seen.clear();
+    for (addr, inline, string) in strings {
+        if seen.insert(addr) {
+            texts.push(StoryText {
+                kind: if inline {
+                    TextKind::Inline
+                } else {
+                    TextKind::String
+                },
+                addr,
+                text: string,
+            });
+        }
+    }
+    Ok(texts)
+}
+
+/// Writes texts as a JSON array of objects, with the fields `kind`, `number` (for abbreviations and objects), `addr` and `text`
+pub fn to_json(texts: &[StoryText]) -> String {
+    let mut json = String::from("[");
+    for (i, text) in texts.iter().enumerate() {
+        if i != 0 {
+            json.push(',');
+        }
+        json.push_str("\n  {\"kind\": \"");
+        match text.kind {
+            TextKind::Abbreviation(n) => {
+                json.push_str(&format!("abbreviation\", \"number\": {}", n))
+            }
+            TextKind::Object(n) => json.push_str(&format!("object\", \"number\": {}", n)),
+            TextKind::Word => json.push_str("word\""),
+            TextKind::Inline => json.push_str("inline\""),
+            TextKind::String => json.push_str("string\""),
+        }
+        json.push_str(&format!(", \"addr\": {}, \"text\": \"", text.addr));
+        for char in text.text.chars() {
+            match char {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                '\0'..='\x1f' => json.push_str(&format!("\\u{:04x}", char as u32)),
+                _ => json.push(char),
+            }
+        }
+        json.push_str("\"}");
+    }
+    json.push_str("\n]\n");
+    json
+}
+
+impl fmt::Display for TextKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TextKind::Abbreviation(n) => write!(f, "abbreviation {}", n),
+            TextKind::Object(n) => write!(f, "object {}", n),
+            TextKind::Word => write!(f, "word"),
+            TextKind::Inline => write!(f, "inline"),
+            TextKind::String => write!(f, "string"),
+        }
+    }
+}
+
+/// The address, the kind and the text on one line, with newlines and backslashes escaped
+impl fmt::Display for StoryText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "${:05x} {}: ", self.addr, self.kind)?;
+        for char in self.text.chars() {
+            match char {
+                '\n' => f.write_str("\\n")?,
+                '\\' => f.write_str("\\\\")?,
+                _ => write!(f, "{}", char)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_format() {
+    let texts = [
+        StoryText {
+            kind: TextKind::Object(3),
+            addr: 0x1234,
+            text: "small \"box\"".to_string(),
+        },
+        StoryText {
+            kind: TextKind::Inline,
+            addr: 0x12345,
+            text: "a\\b\nc".to_string(),
+        },
+    ];
+    assert_eq!(texts[0].to_string(), "$01234 object 3: small \"box\"");
+    assert_eq!(texts[1].to_string(), "$12345 inline: a\\\\b\\nc");
+    assert_eq!(
+        to_json(&texts),
+        "[\n  {\"kind\": \"object\", \"number\": 3, \"addr\": 4660, \"text\": \"small \\\"box\\\"\"},\
+         \n  {\"kind\": \"inline\", \"addr\": 74565, \"text\": \"a\\\\b\\nc\"}\n]\n"
+    );
+    assert_eq!(to_json(&[]), "[\n]\n");
+}
@@ -0,0 +1,525 @@
+//! C interface
+//!
+//! When built as a `cdylib`, this crate can be used from C with the header `include/rzif.h`.
+//! [`rzif_run`] plays the game like [`main`](crate::main), calling back the functions of a [`Callbacks`] structure instead of the methods of an [`Interface`].
+//! The texts are given as UTF-8 bytes with their length, without a terminating zero.
+
+use crate::{err::*, interface::*, Outcome, Story};
+use std::{
+    any::Any,
+    borrow::Cow,
+    ffi::c_void,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+type Text = unsafe extern "C" fn(*mut c_void, *const u8, usize);
+type Word = unsafe extern "C" fn(*mut c_void, u16);
+type Interrupt = unsafe extern "C" fn(*mut c_void, *const u8, usize) -> bool;
+type InterruptChar = unsafe extern "C" fn(*mut c_void) -> bool;
+type Read = unsafe extern "C" fn(
+    *mut c_void,
+    *const u32,
+    usize,
+    *const u8,
+    usize,
+    u16,
+    u16,
+    Interrupt,
+    *mut c_void,
+    *mut u8,
+    usize,
+    *mut usize,
+) -> u32;
+
+/// The configuration of the interface, as [`Config`]
+///
+/// `error_level` is 0 for never, 1 for once, 2 for always, 3 for quit, 4 for pedantic, 5 for forgiving and 6 for frotz (see [`ErrorPolicy`]).
+/// `generator` is 0 for [`Generator::Lcg`] and 1 for [`Generator::Xorshift`].
+#[repr(C)]
+pub struct RzifConfig {
+    status: bool,
+    split: bool,
+    fixed_default: bool,
+    color: bool,
+    bold: bool,
+    italic: bool,
+    fixed: bool,
+    timed: bool,
+    width: u16,
+    height: u16,
+    default_foreground: u8,
+    default_background: u8,
+    true_foreground: u16,
+    true_background: u16,
+    picture: bool,
+    graphics: bool,
+    undo: bool,
+    sound: bool,
+    transparency: bool,
+    error_level: u8,
+    has_seed: bool,
+    seed: u64,
+    generator: u8,
+}
+
+/// The callbacks to the interface, as the methods of [`Interface`], with the `user` pointer given to each one
+///
+/// A null callback does nothing, or fails for the ones returning a result.
+#[repr(C)]
+pub struct Callbacks {
+    user: *mut c_void,
+    write_screen: Option<unsafe extern "C" fn(*mut c_void, *const u8, usize, bool)>,
+    write_transcript: Option<Text>,
+    write_command: Option<Text>,
+    check_unicode: Option<unsafe extern "C" fn(*mut c_void, u32) -> u8>,
+    status: Option<Text>,
+    window_font: Option<unsafe extern "C" fn(*mut c_void, u16) -> bool>,
+    window_color: Option<unsafe extern "C" fn(*mut c_void, u16, u16)>,
+    window_style: Option<Word>,
+    window_set: Option<Word>,
+    window_buffer: Option<Word>,
+    window_split: Option<Word>,
+    window_cursor_set: Option<unsafe extern "C" fn(*mut c_void, u16, u16)>,
+    window_cursor_get: Option<unsafe extern "C" fn(*mut c_void, *mut u16, *mut u16)>,
+    window_erase: Option<Word>,
+    window_line: Option<unsafe extern "C" fn(*mut c_void)>,
+    read: Option<Read>,
+    read_char: Option<unsafe extern "C" fn(*mut c_void, u16, InterruptChar, *mut c_void) -> u32>,
+    read_file: Option<unsafe extern "C" fn(*mut c_void, *mut u8, usize) -> usize>,
+    bleep: Option<Word>,
+    sound: Option<unsafe extern "C" fn(*mut c_void, u16, u16, u16)>,
+    save: Option<unsafe extern "C" fn(*mut c_void, *const u8, usize) -> bool>,
+    restore: Option<unsafe extern "C" fn(*mut c_void, *mut usize) -> *const u8>,
+    restore_failed: Option<Text>,
+    save_aux: Option<unsafe extern "C" fn(*mut c_void, *const u8, usize, *const u8, usize) -> bool>,
+    restore_aux:
+        Option<unsafe extern "C" fn(*mut c_void, *const u8, usize, *mut usize) -> *const u8>,
+    error: Option<unsafe extern "C" fn(*mut c_void, u32, *const u8, usize)>,
+    running: Option<unsafe extern "C" fn(*mut c_void) -> bool>,
+    resized: Option<unsafe extern "C" fn(*mut c_void, *mut u16, *mut u16) -> bool>,
+}
+
+/// A game to play, made by [`rzif_new`]
+pub struct Vm {
+    story: Story,
+    config: Config<'static>,
+    callbacks: Callbacks,
+    /// The message of the fatal error of the last game
+    error: Option<String>,
+}
+
+/// The text of a callback, or an empty text if it is null
+unsafe fn text<'a>(data: *const u8, len: usize) -> Cow<'a, str> {
+    if data.is_null() {
+        Cow::Borrowed("")
+    } else {
+        String::from_utf8_lossy(slice::from_raw_parts(data, len))
+    }
+}
+
+/// The data returned by a callback, or `None` if it is null
+unsafe fn bytes(data: *const u8, len: usize) -> Option<Vec<u8>> {
+    if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len).to_vec())
+    }
+}
+
+/// The message of a panic of the interpreter
+fn panicked(payload: Box<dyn Any + Send>) -> String {
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload.downcast_ref::<String>().map_or("", String::as_str),
+    };
+    format!("Fatal error: the interpreter panicked: {}", message)
+}
+
+// a panic mustn't unwind into the C code calling these, the input just goes on
+unsafe extern "C" fn interrupt<H, F: FnMut(&mut H, &str) -> bool>(
+    data: *mut c_void,
+    input: *const u8,
+    len: usize,
+) -> bool {
+    let (host, routine) = &mut *(data as *mut (*mut H, F));
+    panic::catch_unwind(AssertUnwindSafe(|| routine(&mut **host, &text(input, len))))
+        .unwrap_or(false)
+}
+
+unsafe extern "C" fn interrupt_char<H, F: FnMut(&mut H) -> bool>(data: *mut c_void) -> bool {
+    let (host, routine) = &mut *(data as *mut (*mut H, F));
+    panic::catch_unwind(AssertUnwindSafe(|| routine(&mut **host))).unwrap_or(false)
+}
+
+struct Host<'a>(&'a Callbacks);
+
+impl Interface for Host<'_> {
+    fn write_screen(&mut self, text: &str, fixed: bool) {
+        if let Some(write_screen) = self.0.write_screen {
+            unsafe { write_screen(self.0.user, text.as_ptr(), text.len(), fixed) }
+        }
+    }
+    fn write_transcript(&mut self, text: &str) {
+        if let Some(write_transcript) = self.0.write_transcript {
+            unsafe { write_transcript(self.0.user, text.as_ptr(), text.len()) }
+        }
+    }
+    fn write_command(&mut self, text: &str) {
+        if let Some(write_command) = self.0.write_command {
+            unsafe { write_command(self.0.user, text.as_ptr(), text.len()) }
+        }
+    }
+    fn check_unicode(&mut self, char: char) -> (bool, bool) {
+        match self.0.check_unicode {
+            Some(check_unicode) => {
+                let check = unsafe { check_unicode(self.0.user, char.into()) };
+                (check & 1 != 0, check & 2 != 0)
+            }
+            None => (true, true),
+        }
+    }
+
+    fn status(&mut self, text: &str) {
+        if let Some(status) = self.0.status {
+            unsafe { status(self.0.user, text.as_ptr(), text.len()) }
+        }
+    }
+    fn window_font(&mut self, font: u16) -> bool {
+        match self.0.window_font {
+            Some(window_font) => unsafe { window_font(self.0.user, font) },
+            None => font == 1,
+        }
+    }
+    fn window_color(&mut self, foreground: u16, background: u16) {
+        if let Some(window_color) = self.0.window_color {
+            unsafe { window_color(self.0.user, foreground, background) }
+        }
+    }
+    fn window_style(&mut self, style: u16) {
+        if let Some(window_style) = self.0.window_style {
+            unsafe { window_style(self.0.user, style) }
+        }
+    }
+    fn window_set(&mut self, window: u16) {
+        if let Some(window_set) = self.0.window_set {
+            unsafe { window_set(self.0.user, window) }
+        }
+    }
+    fn window_buffer(&mut self, buffer: u16) {
+        if let Some(window_buffer) = self.0.window_buffer {
+            unsafe { window_buffer(self.0.user, buffer) }
+        }
+    }
+    fn window_split(&mut self, lines: u16) {
+        if let Some(window_split) = self.0.window_split {
+            unsafe { window_split(self.0.user, lines) }
+        }
+    }
+    fn window_cursor_set(&mut self, x: u16, y: u16) {
+        if let Some(window_cursor_set) = self.0.window_cursor_set {
+            unsafe { window_cursor_set(self.0.user, x, y) }
+        }
+    }
+    fn window_cursor_get(&mut self) -> (u16, u16) {
+        let mut cursor = (1, 1);
+        if let Some(window_cursor_get) = self.0.window_cursor_get {
+            unsafe { window_cursor_get(self.0.user, &mut cursor.0, &mut cursor.1) }
+        }
+        cursor
+    }
+    fn window_erase(&mut self, window: u16) {
+        if let Some(window_erase) = self.0.window_erase {
+            unsafe { window_erase(self.0.user, window) }
+        }
+    }
+    fn window_line(&mut self) {
+        if let Some(window_line) = self.0.window_line {
+            unsafe { window_line(self.0.user) }
+        }
+    }
+
+    fn read<F: FnMut(&mut Self, &str) -> bool>(
+        &mut self,
+        terminating: Vec<char>,
+        preload: String,
+        maxlen: u16,
+        time: u16,
+        routine: F,
+    ) -> (String, char) {
+        let Some(read) = self.0.read else {
+            return (String::new(), '\n');
+        };
+        let user = self.0.user;
+        let terminating = terminating.into_iter().map(u32::from).collect::<Vec<_>>();
+        // each char takes at most 4 bytes in UTF-8
+        let mut buffer = vec![0; usize::from(maxlen) * 4];
+        let mut len = 0;
+        let mut data = (self as *mut Self, routine);
+        let terminator = unsafe {
+            read(
+                user,
+                terminating.as_ptr(),
+                terminating.len(),
+                preload.as_ptr(),
+                preload.len(),
+                maxlen,
+                time,
+                interrupt::<Self, F>,
+                &mut data as *mut _ as *mut c_void,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut len,
+            )
+        };
+        buffer.truncate(len);
+        (
+            String::from_utf8_lossy(&buffer).into_owned(),
+            char::from_u32(terminator).unwrap_or('\n'),
+        )
+    }
+    fn read_char<F: FnMut(&mut Self) -> bool>(&mut self, time: u16, routine: F) -> char {
+        let Some(read_char) = self.0.read_char else {
+            return '\n';
+        };
+        let user = self.0.user;
+        let mut data = (self as *mut Self, routine);
+        let char = unsafe {
+            read_char(
+                user,
+                time,
+                interrupt_char::<Self, F>,
+                &mut data as *mut _ as *mut c_void,
+            )
+        };
+        char::from_u32(char).unwrap_or('\n')
+    }
+    fn read_file(&mut self) -> String {
+        let Some(read_file) = self.0.read_file else {
+            return String::new();
+        };
+        let mut buffer = vec![0; 1024];
+        let len = unsafe { read_file(self.0.user, buffer.as_mut_ptr(), buffer.len()) };
+        buffer.truncate(len);
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+
+    fn bleep(&mut self, bleep: u16) {
+        if let Some(callback) = self.0.bleep {
+            unsafe { callback(self.0.user, bleep) }
+        }
+    }
+    fn sound(&mut self, number: u16, effect: u16, volume: u16) {
+        if let Some(sound) = self.0.sound {
+            unsafe { sound(self.0.user, number, effect, volume) }
+        }
+    }
+    fn save(&mut self, data: &[u8]) -> bool {
+        match self.0.save {
+            Some(save) => unsafe { save(self.0.user, data.as_ptr(), data.len()) },
+            None => false,
+        }
+    }
+    fn restore(&mut self) -> Vec<u8> {
+        let Some(restore) = self.0.restore else {
+            return Vec::new();
+        };
+        let mut len = 0;
+        unsafe { bytes(restore(self.0.user, &mut len), len) }.unwrap_or_default()
+    }
+    fn restore_failed(&mut self, cause: SaveError) {
+        if let Some(restore_failed) = self.0.restore_failed {
+            let cause = cause.to_string();
+            unsafe { restore_failed(self.0.user, cause.as_ptr(), cause.len()) }
+        }
+    }
+    fn save_aux(&mut self, name: &str, data: &[u8]) -> bool {
+        match self.0.save_aux {
+            Some(save_aux) => unsafe {
+                save_aux(
+                    self.0.user,
+                    name.as_ptr(),
+                    name.len(),
+                    data.as_ptr(),
+                    data.len(),
+                )
+            },
+            None => false,
+        }
+    }
+    fn restore_aux(&mut self, name: &str) -> Option<Vec<u8>> {
+        let restore_aux = self.0.restore_aux?;
+        let mut len = 0;
+        unsafe {
+            bytes(
+                restore_aux(self.0.user, name.as_ptr(), name.len(), &mut len),
+                len,
+            )
+        }
+    }
+    fn error(&mut self, error: Error) {
+        if let Some(callback) = self.0.error {
+            let text = error.to_string();
+            unsafe { callback(self.0.user, error.cause as u32, text.as_ptr(), text.len()) }
+        }
+    }
+    fn running(&mut self) -> bool {
+        match self.0.running {
+            Some(running) => unsafe { running(self.0.user) },
+            None => true,
+        }
+    }
+    fn resized(&mut self) -> Option<(u16, u16)> {
+        let resized = self.0.resized?;
+        let mut size = (0, 0);
+        unsafe { resized(self.0.user, &mut size.0, &mut size.1) }.then_some(size)
+    }
+}
+
+/// Writes the default configuration: every feature but pictures, sound and transparency, a 80×24 screen and errors always reported
+///
+/// # Safety
+/// `config` must point to a `RzifConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn rzif_default_config(config: *mut RzifConfig) {
+    *config = RzifConfig {
+        status: true,
+        split: true,
+        fixed_default: true,
+        color: true,
+        bold: true,
+        italic: true,
+        fixed: true,
+        timed: true,
+        width: 80,
+        height: 24,
+        default_foreground: 2,
+        default_background: 9,
+        true_foreground: 0x0000,
+        true_background: 0x7fff,
+        picture: false,
+        graphics: true,
+        undo: true,
+        sound: false,
+        transparency: false,
+        error_level: 2,
+        has_seed: false,
+        seed: 0,
+        generator: 1,
+    };
+}
+
+/// Makes a game from the `len` bytes of a storyfile or a Blorb file, to play with [`rzif_run`]
+///
+/// The story and the callbacks are copied. Returns null if `story` or `callbacks` is null.
+///
+/// # Safety
+/// `story` must point to `len` bytes, `config` to a `RzifConfig` or be null for the default one, and `callbacks` to a `Callbacks`.
+#[no_mangle]
+pub unsafe extern "C" fn rzif_new(
+    story: *const u8,
+    len: usize,
+    config: *const RzifConfig,
+    callbacks: *const Callbacks,
+) -> *mut Vm {
+    let (Some(story), false) = (bytes(story, len), callbacks.is_null()) else {
+        return ptr::null_mut();
+    };
+    let mut default = std::mem::zeroed();
+    rzif_default_config(&mut default);
+    let config = config.as_ref().unwrap_or(&default);
+    let config = Config {
+        status: config.status,
+        split: config.split,
+        fixed_default: config.fixed_default,
+        color: config.color,
+        bold: config.bold,
+        italic: config.italic,
+        fixed: config.fixed,
+        timed: config.timed,
+        screen: (config.width, config.height),
+        default_color: (config.default_foreground, config.default_background),
+        true_color: (config.true_foreground, config.true_background),
+        picture: config.picture,
+        graphics: config.graphics,
+        undo: config.undo,
+        sound: config.sound,
+        transparency: config.transparency,
+        patches: &[],
+        error: match config.error_level {
+            0 => ErrorPolicy::new(ErrorLevel::Never),
+            1 => ErrorPolicy::new(ErrorLevel::Once),
+            3 => ErrorPolicy::new(ErrorLevel::Quit),
+            4 => ErrorPolicy::pedantic(),
+            5 => ErrorPolicy::forgiving(),
+            6 => ErrorPolicy::frotz(),
+            _ => ErrorPolicy::new(ErrorLevel::Always),
+        },
+        seed: config.has_seed.then_some(config.seed),
+        generator: match config.generator {
+            0 => Generator::Lcg,
+            _ => Generator::Xorshift,
+        },
+    };
+    let callbacks = ptr::read(callbacks);
+    Box::into_raw(Box::new(Vm {
+        story: story.into(),
+        config,
+        callbacks,
+        error: None,
+    }))
+}
+
+/// Plays the game until its end, and returns how it ended: 0 if it quit, 1 if it was stopped, 2 on a fatal error (see [`rzif_error`])
+///
+/// A panic of the interpreter is caught and ends the game like a fatal error.
+///
+/// The game can be played again, from its start.
+///
+/// # Safety
+/// `vm` must come from [`rzif_new`], and the callbacks must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn rzif_run(vm: *mut Vm) -> i32 {
+    let vm = &mut *vm;
+    vm.error = None;
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        crate::main(vm.story.clone(), vm.config, &mut Host(&vm.callbacks))
+    }));
+    match outcome {
+        Ok(Outcome::Quit) => 0,
+        Ok(Outcome::Stopped) => 1,
+        Ok(Outcome::Fatal(error)) => {
+            vm.error = Some(error.to_string());
+            2
+        }
+        Err(payload) => {
+            vm.error = Some(panicked(payload));
+            2
+        }
+    }
+}
+
+/// Writes the fatal error of the last game into `buffer`, up to `capacity` bytes. Returns the length of the message, 0 if there was no fatal error.
+///
+/// # Safety
+/// `vm` must come from [`rzif_new`], and `buffer` must point to `capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rzif_error(vm: *const Vm, buffer: *mut u8, capacity: usize) -> usize {
+    let Some(text) = &(*vm).error else {
+        return 0;
+    };
+    let len = text.len().min(capacity);
+    ptr::copy_nonoverlapping(text.as_ptr(), buffer, len);
+    text.len()
+}
+
+/// Frees a game made by [`rzif_new`]
+///
+/// # Safety
+/// `vm` must come from [`rzif_new`] or be null, and mustn't be used after.
+#[no_mangle]
+pub unsafe extern "C" fn rzif_free(vm: *mut Vm) {
+    if !vm.is_null() {
+        drop(Box::from_raw(vm));
+    }
+}
@@ -0,0 +1,273 @@
+//! Headless replay of whole playthroughs
+//!
+//! [`replay`] plays a game with the commands of a walkthrough and a fixed random seed, and records what the game printed.
+//! The resulting [`Transcript`] can then be compared to a golden file with [`Transcript::diff`], to regression-test a game after each rebuild.
+//! [`profile`] does the same while measuring the time spent in each opcode, to benchmark the interpreter.
+
+use crate::{blorb, err::*, interface::*, machine, mem::Story, opcode};
+use std::{cmp::Reverse, fmt, time::Duration};
+
+static HEADLESS: Config<'static> = Config {
+    status: true,
+    split: true,
+    fixed_default: true,
+    color: true,
+    bold: true,
+    italic: true,
+    fixed: true,
+    timed: false,
+    screen: (80, 24),
+    default_color: (2, 9),
+    true_color: (0x0000, 0x7fff),
+    picture: false,
+    graphics: true,
+    undo: true,
+    sound: false,
+    transparency: false,
+    patches: &[],
+    error: ErrorPolicy::new(ErrorLevel::Always),
+    seed: None,
+    generator: Generator::Xorshift,
+};
+
+/// What the game printed during a replay
+///
+/// Its [`Display`](fmt::Display) form is the text of the game, with each command after the prompt, as it would appear on a terminal.
+#[derive(Clone, Debug, Default)]
+pub struct Transcript {
+    /// The turns of the game. The first one holds the text printed before the first input.
+    pub turns: Vec<Turn>,
+    /// The errors reported by the interpreter. The last one is why the game ended.
+    pub errors: Vec<Error>,
+}
+
+/// A turn of a [`Transcript`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Turn {
+    /// The command that started this turn (`None` for the first turn)
+    pub command: Option<String>,
+    /// The text printed by the game in response
+    pub output: String,
+}
+
+/// The instructions executed during a replay, made by [`profile`]
+#[derive(Clone, Debug)]
+pub struct Profile {
+    /// What the game printed
+    pub transcript: Transcript,
+    /// The number of instructions executed
+    pub instructions: u64,
+    /// The opcodes executed, with their number of executions and their total time, from the slowest
+    pub opcodes: Vec<(&'static str, u64, Duration)>,
+}
+
+/// Where a [`Transcript`] first differs from a golden file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The index of the diverging turn in [`Transcript::turns`]
+    pub turn: usize,
+    /// The command that started the diverging turn
+    pub command: Option<String>,
+    /// The number of the diverging line, starting at 1
+    pub line: usize,
+    /// The line in the golden file
+    pub expected: String,
+    /// The line in the transcript
+    pub found: String,
+}
+
+impl Transcript {
+    /// Compare this transcript with the text of a `golden` file. Returns `None` if they are the same.
+    pub fn diff(&self, golden: &str) -> Option<Divergence> {
+        let text = self.to_string();
+        let mut offset = text
+            .bytes()
+            .zip(golden.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if offset == text.len() && offset == golden.len() {
+            return None;
+        }
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let mut turn = 0;
+        let mut start = 0;
+        for (i, t) in self.turns.iter().enumerate() {
+            if start > offset {
+                break;
+            }
+            turn = i;
+            if let Some(ref command) = t.command {
+                start += command.len();
+            }
+            start += t.output.len();
+        }
+
+        Some(Divergence {
+            turn,
+            command: self.turns.get(turn).and_then(|t| t.command.clone()),
+            line: text[..offset].matches('\n').count() + 1,
+            expected: line(golden, offset),
+            found: line(&text, offset),
+        })
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for turn in self.turns.iter() {
+            if let Some(ref command) = turn.command {
+                write!(f, "{}", command)?;
+            }
+            write!(f, "{}", turn.output)?;
+        }
+        Ok(())
+    }
+}
+
+fn line(text: &str, offset: usize) -> String {
+    let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+    text[start..end].to_string()
+}
+
+/// Play the `story` headlessly with the commands of a `walkthrough`, using a fixed random `seed`.
+///
+/// The walkthrough holds one command per line. Lines starting with `# ` are comments, so a lone `#` is still a key.
+/// When the game reads a single key, the first char of the next command is used (or a newline if the command is empty).
+/// The game is stopped when there is no more commands.
+/// The screen is 80 columns wide and 24 lines high.
+pub fn replay(story: impl Into<Story>, walkthrough: &str, seed: u64) -> Transcript {
+    play(story.into(), walkthrough, seed, &mut None)
+}
+
+/// Play the `story` like [`replay`], measuring the time spent in each opcode
+///
+/// Measuring slows the interpreter down, time [`replay`] to know its speed.
+pub fn profile(story: impl Into<Story>, walkthrough: &str, seed: u64) -> Profile {
+    let story = story.into();
+    let story = blorb::story(story.clone()).unwrap_or(story);
+    let v = story.first().copied().unwrap_or(0);
+    let mut counts = Some(Vec::new());
+    let transcript = play(story, walkthrough, seed, &mut counts);
+    let mut opcodes = Vec::new();
+    for (i, &(count, time)) in counts.unwrap_or_default().iter().enumerate() {
+        if count != 0 {
+            opcodes.push((opcode::name(v, (i >> 8) as u8, i as u8), count, time));
+        }
+    }
+    opcodes.sort_by_key(|opcode| Reverse(opcode.2));
+    Profile {
+        transcript,
+        instructions: opcodes.iter().map(|opcode| opcode.1).sum(),
+        opcodes,
+    }
+}
+
+fn play(
+    story: Story,
+    walkthrough: &str,
+    seed: u64,
+    counts: &mut Option<Vec<(u64, Duration)>>,
+) -> Transcript {
+    let mut headless = Headless {
+        commands: walkthrough
+            .lines()
+            .filter(|line| !line.starts_with("# "))
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .into_iter(),
+        transcript: Transcript {
+            turns: vec![Turn::default()],
+            errors: Vec::new(),
+        },
+        done: false,
+    };
+    let config = Config {
+        seed: Some(seed),
+        ..HEADLESS
+    };
+    let error = machine::init_counting(story, config, &mut headless, counts).unwrap_err();
+    headless.transcript.errors.push(error);
+    headless.transcript
+}
+
+struct Headless {
+    commands: std::vec::IntoIter<String>,
+    transcript: Transcript,
+    done: bool,
+}
+
+impl Headless {
+    fn next(&mut self) -> Option<String> {
+        let command = self.commands.next();
+        match command {
+            Some(ref command) => self.transcript.turns.push(Turn {
+                command: Some(command.clone()),
+                output: String::new(),
+            }),
+            None => self.done = true,
+        }
+        command
+    }
+}
+
+impl Interface for Headless {
+    fn write_screen(&mut self, text: &str, _: bool) {
+        self.transcript.turns.last_mut().unwrap().output += text;
+    }
+    fn write_transcript(&mut self, _: &str) {}
+    fn write_command(&mut self, _: &str) {}
+
+    fn status(&mut self, _: &str) {}
+    fn window_font(&mut self, font: u16) -> bool {
+        matches!(font, 1 | 4)
+    }
+    fn window_color(&mut self, _: u16, _: u16) {}
+    fn window_style(&mut self, _: u16) {}
+    fn window_set(&mut self, _: u16) {}
+    fn window_buffer(&mut self, _: u16) {}
+    fn window_split(&mut self, _: u16) {}
+    fn window_cursor_set(&mut self, _: u16, _: u16) {}
+    fn window_cursor_get(&mut self) -> (u16, u16) {
+        (1, 1)
+    }
+    fn window_erase(&mut self, _: u16) {}
+    fn window_line(&mut self) {}
+
+    fn read<F: FnMut(&mut Self, &str) -> bool>(
+        &mut self,
+        _: Vec<char>,
+        _: String,
+        _: u16,
+        _: u16,
+        _: F,
+    ) -> (String, char) {
+        (self.next().unwrap_or_default(), '\n')
+    }
+    fn read_char<T: FnMut(&mut Self) -> bool>(&mut self, _: u16, _: T) -> char {
+        self.next()
+            .and_then(|command| command.chars().next())
+            .unwrap_or('\n')
+    }
+    fn read_file(&mut self) -> String {
+        String::new()
+    }
+
+    fn bleep(&mut self, _: u16) {}
+    fn save(&mut self, _: &[u8]) -> bool {
+        false
+    }
+    fn restore(&mut self) -> Vec<u8> {
+        Vec::new()
+    }
+    fn restore_failed(&mut self, _: SaveError) {}
+    fn error(&mut self, error: Error) {
+        self.transcript.errors.push(error);
+    }
+    fn running(&mut self) -> bool {
+        !self.done
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{blorb, dict, err::*, mem, obj, text};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Information about a storyfile, see [`info`]
+pub struct StoryInfo {
+    /// the version of the z-machine
+    pub version: u8,
+    /// the release number
+    pub release: u16,
+    /// the serial code (usually the compilation date, as YYMMDD)
+    pub serial: String,
+    /// the checksum written in the header
+    pub checksum: u16,
+    /// the identifier of the story, as defined by the [Treaty of Babel](https://babel.ifarchive.org/)
+    pub ifid: String,
+    /// the version of the Inform compiler (`None` if it wasn't compiled by Inform)
+    pub inform: Option<String>,
+    /// the number of abbreviations
+    pub abbreviations: usize,
+    /// the number of words in the dictionary
+    pub dictionary: usize,
+    /// the number of objects
+    pub objects: usize,
+    /// the title from the iFiction metadata (`None` if it wasn't in a Blorb file with metadata)
+    pub title: Option<String>,
+}
+
+/// Reads the information about a storyfile
+///
+/// The file can be a storyfile or a Blorb file containing one.
+/// This returns the same error as [`main`](crate::main) if the storyfile can't be played.
+pub fn info(file: &[u8]) -> Result<StoryInfo, Error> {
+    let title = if blorb::is_blorb(file) {
+        blorb::chunk(file, b"IFmd").and_then(title)
+    } else {
+        None
+    };
+    let mem = mem::new(blorb::story(file)?)?;
+    let version = mem[0x00];
+    let release = mem.loadw(0x02)?;
+    let serial = mem.story()[0x12..0x18]
+        .iter()
+        .map(|&c| {
+            if c.is_ascii_alphanumeric() {
+                c as char
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let checksum = mem.loadw(0x1c)?;
+
+    let ifid = match uuid(mem.story()) {
+        Some(uuid) => uuid,
+        // the checksum is omitted for old Infocom games
+        None if serial == "000000"
+            || serial.starts_with('8')
+            || !serial.starts_with(char::is_numeric) =>
+        {
+            format!("ZCODE-{}-{}", release, serial)
+        }
+        None => format!("ZCODE-{}-{}-{:04X}", release, serial, checksum),
+    };
+
+    let inform = &mem.story()[0x3c..0x40];
+    let inform = if inform.iter().all(u8::is_ascii_graphic) {
+        Some(inform.iter().map(|&c| c as char).collect())
+    } else {
+        None
+    };
+
+    let abbreviations = text::abbreviations(&mem) as usize;
+    let dictionary = dict::init(&mem).count(&mem).unwrap_or(0);
+    let objects = obj::init(&mem).count(&mem).unwrap_or(0);
+
+    Ok(StoryInfo {
+        version,
+        release,
+        serial,
+        checksum,
+        ifid,
+        inform,
+        abbreviations,
+        dictionary,
+        objects,
+        title,
+    })
+}
+
+/// The IFID declared in the story, as "UUID://...//"
+fn uuid(mem: &[u8]) -> Option<String> {
+    mem.windows(45).find_map(|window| {
+        let uuid = &window[7..43];
+        if &window[..7] == b"UUID://"
+            && &window[43..] == b"//"
+            && uuid.iter().all(|&c| c.is_ascii_hexdigit() || c == b'-')
+        {
+            Some(
+                uuid.iter()
+                    .map(|&c| c.to_ascii_uppercase() as char)
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    })
+}
+
+/// The title in iFiction metadata
+fn title(xml: &[u8]) -> Option<String> {
+    let xml = String::from_utf8_lossy(xml);
+    let start = xml.find("<title>")? + 7;
+    let len = xml[start..].find("</title>")?;
+    let title = xml[start..start + len]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    Some(title)
+}
+
+#[test]
+fn test_info() {
+    let mut data = mem::default();
+    data[0x00] = 3;
+    data[0x03] = 88;
+    data[0x12..0x18].copy_from_slice(b"840726");
+    data[0x1c] = 0x12;
+    data[0x1d] = 0xab;
+    data[0x0b] = 0x40;
+    data[0x09] = 0x40 + 62 + 2 * 9;
+    data[0x18] = 0x10;
+    data.extend(vec![0; 62]);
+    // two objects, the first property table follows the second
+    data.extend(&[0, 0, 0, 0, 0, 0, 0, 0, 0x90]);
+    data.extend(&[0, 0, 0, 0, 0, 0, 0, 0, 0x90]);
+    // dictionary: no separators, 3 words
+    data.extend(&[0, 7, 0, 3]);
+    data.extend(&[0; 21]);
+    let story = data.clone();
+    let info = info(&data).unwrap();
+    assert_eq!(info.version, 3);
+    assert_eq!(info.release, 88);
+    assert_eq!(info.serial, "840726");
+    assert_eq!(info.checksum, 0x12ab);
+    assert_eq!(info.ifid, "ZCODE-88-840726");
+    assert_eq!(info.inform, None);
+    assert_eq!(info.abbreviations, 96);
+    assert_eq!(info.dictionary, 3);
+    assert_eq!(info.objects, 2);
+    assert_eq!(info.title, None);
+
+    data[0x12..0x18].copy_from_slice(b"010203");
+    data[0x3c..0x40].copy_from_slice(b"6.31");
+    let info = self::info(&data).unwrap();
+    assert_eq!(info.ifid, "ZCODE-88-010203-12AB");
+    assert_eq!(info.inform, Some("6.31".to_string()));
+
+    data.extend(b"UUID://0123abcd-0000-0000-0000-0123456789AB//");
+    assert_eq!(
+        self::info(&data).unwrap().ifid,
+        "0123ABCD-0000-0000-0000-0123456789AB"
+    );
+
+    let metadata = b"<ifindex><story><bibliographic>\n<title>Tom &amp;\n Jerry</title>";
+    let file = blorb::blorb(&[(b"IFmd", metadata), (b"ZCOD", &story)]);
+    let info = self::info(&file).unwrap();
+    assert_eq!(info.title, Some("Tom & Jerry".to_string()));
+    assert_eq!(info.release, 88);
+    assert_eq!(self::info(&[0; 4]).unwrap_err().cause, Cause::TooShort);
+}
+
+#[test]
+fn test_czech() {
+    let story = include_bytes!("../../zcode/czech/czech.z5");
+    let mut info = StoryInfo {
+        version: 5,
+        release: 1,
+        serial: "180521".to_string(),
+        checksum: 0xf4dd,
+        ifid: "ZCODE-1-180521-F4DD".to_string(),
+        inform: Some("6.31".to_string()),
+        abbreviations: 96,
+        dictionary: 0,
+        objects: 10,
+        title: None,
+    };
+    assert_eq!(self::info(story).unwrap(), info);
+
+    let metadata = b"<ifindex><story><bibliographic><title>Czech</title>";
+    let file = blorb::blorb(&[(b"IFmd", metadata), (b"ZCOD", story)]);
+    info.title = Some("Czech".to_string());
+    assert_eq!(self::info(&file).unwrap(), info);
+
+    let info = self::info(include_bytes!("../../zcode/strictz/strictz.z5")).unwrap();
+    assert_eq!(info.ifid, "ZCODE-1-180521-C636");
+    assert_eq!(info.dictionary, 4);
+    assert_eq!(info.objects, 7);
+}
@@ -1,10 +1,12 @@
-pub use crate::err::{Cause, Error, SaveError, Trace};
+use crate::err::CAUSE_COUNT;
+pub use crate::err::{Call, Cause, Details, Error, Instruction, SaveError, Trace};
+use crate::patch::Patch;
 
 /// The configuration of your interface
 ///
 /// This tell to the interpreter what your interface can do. It use it to set/clear header flags. See the [`Interface`] trait and [the format of the header](http://inform-fiction.org/zmachine/standards/z1point1/sect11.html) for more information.
 #[derive(Clone, Copy, Debug)]
-pub struct Config {
+pub struct Config<'a> {
     /// Can your interface draw a status line ?
     pub status: bool,
     /// Can your interface split the screen ?
@@ -29,12 +31,26 @@ pub struct Config {
     pub true_color: (u16, u16),
     /// Do your interface support [character graphics font](http://inform-fiction.org/zmachine/standards/z1point1/sect16.html) ?
     pub picture: bool,
-    /// When to report errors (see [`ErrorLevel`])
-    pub error: ErrorLevel,
+    /// Should the interpreter draw the [character graphics font](crate::doc#fonts) itself, with unicode box-drawing, block and arrow characters ?
+    pub graphics: bool,
+    /// Can the game [undo](crate::doc#capabilities) a turn ? Undo keeps a copy of the memory of the game.
+    pub undo: bool,
+    /// Can your interface play the [sound effects](crate::doc#capabilities) of the game, with [`Interface::sound`] ?
+    pub sound: bool,
+    /// Can your interface draw the background color as [transparent](crate::doc#capabilities) ?
+    pub transparency: bool,
+    /// Compatibility patches to apply with the built-in ones (see [`parse_patches`](crate::parse_patches))
+    pub patches: &'a [Patch],
+    /// How to handle errors (see [`ErrorPolicy`])
+    pub error: ErrorPolicy,
+    /// The seed of the random number generator, for reproducible playthroughs (`None` to seed it from the clock)
+    pub seed: Option<u64>,
+    /// Which algorithm generates random numbers (see [`Generator`])
+    pub generator: Generator,
 }
 
 /// When to report errors
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ErrorLevel {
     /// Never report any error
     Never,
@@ -46,8 +62,89 @@ pub enum ErrorLevel {
     Quit,
 }
 
+/// How to handle each [`Cause`] of error
+///
+/// Fatal errors always end the game. For the others, the policy gives the [`ErrorLevel`] and the fallback value: when an instruction that stores a result fails and the error isn't fatal, the fallback is stored instead (0 unless set otherwise).
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorPolicy {
+    levels: [ErrorLevel; CAUSE_COUNT],
+    fallbacks: [u16; CAUSE_COUNT],
+}
+
+impl ErrorPolicy {
+    /// Handle every error with the same `level`
+    pub const fn new(level: ErrorLevel) -> ErrorPolicy {
+        ErrorPolicy {
+            levels: [level; CAUSE_COUNT],
+            fallbacks: [0; CAUSE_COUNT],
+        }
+    }
+
+    /// For game authors: quit on any error
+    pub const fn pedantic() -> ErrorPolicy {
+        ErrorPolicy::new(ErrorLevel::Quit)
+    }
+
+    /// For players: never report errors, and keep playing
+    ///
+    /// Chars read through a broken unicode table become `?`.
+    pub const fn forgiving() -> ErrorPolicy {
+        ErrorPolicy::new(ErrorLevel::Never).with_fallback(Cause::UnicodeOut, b'?' as u16)
+    }
+
+    /// Like Frotz: quit on the errors it considers fatal, report the others once
+    ///
+    /// References to object 0 are only reported once, as Frotz warns about them and keeps playing.
+    /// Chars read through a broken unicode table become `?`, as Frotz does with unknown chars.
+    pub const fn frotz() -> ErrorPolicy {
+        ErrorPolicy::new(ErrorLevel::Once)
+            .with_level(Cause::WriteOut, ErrorLevel::Quit)
+            .with_level(Cause::DivByZero, ErrorLevel::Quit)
+            .with_level(Cause::StackUnderflow, ErrorLevel::Quit)
+            .with_level(Cause::TextBufferOut, ErrorLevel::Quit)
+            .with_level(Cause::BadAttr, ErrorLevel::Quit)
+            .with_level(Cause::NoProp, ErrorLevel::Quit)
+            .with_level(Cause::OutputS3Overflow, ErrorLevel::Quit)
+            .with_level(Cause::SaveInterrupt, ErrorLevel::Quit)
+            .with_fallback(Cause::UnicodeOut, b'?' as u16)
+    }
+
+    /// Handle the errors with this `cause` with another `level`
+    pub const fn with_level(mut self, cause: Cause, level: ErrorLevel) -> ErrorPolicy {
+        self.levels[cause as usize] = level;
+        self
+    }
+
+    /// Store another `value` when an instruction fails with this `cause`
+    pub const fn with_fallback(mut self, cause: Cause, value: u16) -> ErrorPolicy {
+        self.fallbacks[cause as usize] = value;
+        self
+    }
+
+    /// When to report the errors with this `cause`
+    pub fn level(&self, cause: Cause) -> ErrorLevel {
+        self.levels[cause as usize]
+    }
+
+    /// What to store when an instruction fails with this `cause`
+    pub fn fallback(&self, cause: Cause) -> u16 {
+        self.fallbacks[cause as usize]
+    }
+}
+
+/// Random number generators
+///
+/// Whatever the generator, a game can ask for predictable numbers with a negative seed, as described [here](http://inform-fiction.org/zmachine/standards/z1point1/sect02.html#four).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generator {
+    /// A linear congruential generator
+    Lcg,
+    /// A xorshift generator, with better statistical properties
+    Xorshift,
+}
+
 #[cfg(test)]
-pub static DEFAULT: Config = Config {
+pub static DEFAULT: Config<'static> = Config {
     status: true,
     split: true,
     fixed_default: true,
@@ -60,7 +157,14 @@ pub static DEFAULT: Config = Config {
     default_color: (0, 0),
     true_color: (0, 0),
     picture: false,
-    error: ErrorLevel::Always,
+    graphics: false,
+    undo: true,
+    sound: false,
+    transparency: false,
+    patches: &[],
+    error: ErrorPolicy::new(ErrorLevel::Always),
+    seed: None,
+    generator: Generator::Lcg,
 };
 
 /// Callbacks to your interface
@@ -74,6 +178,10 @@ pub trait Interface {
     fn write_transcript(&mut self, text: &str);
     /// Write a `text` to the [commands file](crate::doc#command-file).
     fn write_command(&mut self, text: &str);
+    /// Can your interface print (first) and read (second) the unicode `char` ? The chars that can't be printed are replaced by their [textual equivalent](crate::doc#unicode-characters).
+    fn check_unicode(&mut self, _char: char) -> (bool, bool) {
+        (true, true)
+    }
 
     /// Write the `text` in the status line (the top line of the screen).
     fn status(&mut self, text: &str);
@@ -99,7 +207,9 @@ pub trait Interface {
     fn window_line(&mut self);
 
     /// Read a line treminated by a `terminating` character, with the text `preload` already given and of maximum length `maxlen`.
-    fn read<F: FnMut(&mut Self) -> bool>(
+    ///
+    /// Echo the chars as they are typed, but not the terminating character: the interpreter prints the newline itself. Every `time` tenths of seconds (if not 0), the `routine` must be called with the text typed so far, with the cursor at the end of the input line; it returns true if the input must be stopped. If the routine printed something, the interpreter writes the input again after it, so that the cursor is back at the end of the input line. See [input](crate::doc#input).
+    fn read<F: FnMut(&mut Self, &str) -> bool>(
         &mut self,
         terminating: Vec<char>,
         preload: String,
@@ -114,14 +224,33 @@ pub trait Interface {
 
     /// Emit the given [`bleep`](crate::doc#bleeps).
     fn bleep(&mut self, bleep: u16);
+    /// Prepare (`effect` 1), play (2), stop (3) or unload (4) the [sound effect](crate::doc#capabilities) `number`, at the `volume` from 1 to 8 (255 for the loudest). Only called if [`Config::sound`] is set.
+    fn sound(&mut self, _number: u16, _effect: u16, _volume: u16) {}
     /// [Save](crate::doc#save) some `data` into a file. Returns true in case of success, false otherwise.
     fn save(&mut self, data: &[u8]) -> bool;
     /// [Restore](crate::doc#save) some data from a file. In case of failure an empty `Vec` can be returned.
     fn restore(&mut self) -> Vec<u8>;
     /// Called if the resore fail
     fn restore_failed(&mut self, cause: SaveError);
-    /// Called on error
+    /// [Save](crate::doc#auxiliary-files) some `data` into the auxiliary file `name`, or a file chosen by the player if `name` is empty. Returns true in case of success, false otherwise.
+    fn save_aux(&mut self, _name: &str, _data: &[u8]) -> bool {
+        false
+    }
+    /// [Restore](crate::doc#auxiliary-files) the data of the auxiliary file `name`, or of a file chosen by the player if `name` is empty. Returns `None` in case of failure.
+    fn restore_aux(&mut self, _name: &str) -> Option<Vec<u8>> {
+        None
+    }
+    /// Called on the errors the game goes on after, as the [`ErrorPolicy`] says. The error that ends the game is returned by [`main`](crate::main) instead.
     fn error(&mut self, error: Error);
+    /// Called before each instruction. Returning false stops the game, and [`main`](crate::main) returns [`Outcome::Stopped`](crate::Outcome::Stopped).
+    fn running(&mut self) -> bool {
+        true
+    }
+    /// Called before each instruction. Returns the new (width; height) of the screen, in characters, if it was resized since the last call.
+    /// The interpreter writes it to the header, where the game can read it, and uses it for the status line.
+    fn resized(&mut self) -> Option<(u16, u16)> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +258,9 @@ pub trait Interface {
     fn write_screen(&mut self, _: &str, _: bool) {}
     fn write_transcript(&mut self, _: &str) {}
     fn write_command(&mut self, _: &str) {}
+    fn check_unicode(&mut self, _: char) -> (bool, bool) {
+        (true, true)
+    }
 
     fn status(&mut self, _: &str) {}
     fn window_font(&mut self, _: u16) -> bool {
@@ -146,7 +278,7 @@ pub trait Interface {
     fn window_erase(&mut self, _: u16) {}
     fn window_line(&mut self) {}
 
-    fn read<T: FnMut(&mut Self) -> bool>(
+    fn read<T: FnMut(&mut Self, &str) -> bool>(
         &mut self,
         _: Vec<char>,
         _: String,
@@ -164,6 +296,7 @@ pub trait Interface {
     }
 
     fn bleep(&mut self, _: u16) {}
+    fn sound(&mut self, _: u16, _: u16, _: u16) {}
     fn save(&mut self, _: &[u8]) -> bool {
         false
     }
@@ -171,7 +304,19 @@ pub trait Interface {
         Vec::new()
     }
     fn restore_failed(&mut self, _: SaveError) {}
+    fn save_aux(&mut self, _: &str, _: &[u8]) -> bool {
+        false
+    }
+    fn restore_aux(&mut self, _: &str) -> Option<Vec<u8>> {
+        None
+    }
     fn error(&mut self, error: Error) {
         panic!("Error: {:?}", error);
     }
+    fn running(&mut self) -> bool {
+        true
+    }
+    fn resized(&mut self) -> Option<(u16, u16)> {
+        None
+    }
 }
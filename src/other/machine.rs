@@ -1,10 +1,28 @@
 use crate::{err::*, *};
+use std::time::{Duration, Instant};
 
-pub fn init<I: Interface>(data: Vec<u8>, config: Config, interface: &mut I) -> Result<(), Error> {
+pub fn init<I: Interface>(
+    data: mem::Story,
+    config: Config,
+    interface: &mut I,
+) -> Result<(), Error> {
     // or Result<!, Error>
-    let mut mem = fatal(mem::new(data))?;
+    init_counting(data, config, interface, &mut None)
+}
+
+/// Like [`init`], counting the executions and the time of each opcode if `counts` is `Some`
+///
+/// The counts are indexed by `count << 8 | opcode`.
+pub fn init_counting<I: Interface>(
+    data: mem::Story,
+    config: Config,
+    interface: &mut I,
+    counts: &mut Option<Vec<(u64, Duration)>>,
+) -> Result<(), Error> {
+    let mut mem = fatal(blorb::story(data).and_then(mem::new))?;
     let header = fatal(header::init(&mut mem, &config))?;
-    let mut rand = alu::init();
+    patch::apply(&mut mem, &header, &config);
+    let mut rand = alu::init(&config);
     let text = fatal(text::init(&mem, &header))?;
     let mut state = state::init(&mem);
     let mut out = out::init();
@@ -12,10 +30,14 @@ pub fn init<I: Interface>(data: Vec<u8>, config: Config, interface: &mut I) -> R
     let mut input = input::init(&mem);
     let obj = obj::init(&mem);
     let dict = dict::init(&mem);
-    let restart = state.save(&mem).unwrap();
-    let mut undo = [state.save(&mem).unwrap()];
+    let restart = state.save(&mem, &rand).unwrap();
+    let mut undo = [state.save(&mem, &rand).unwrap()];
     let mut err_said = [false; CAUSE_COUNT];
-    run(
+    state.counts = counts.take().map(|mut counts| {
+        counts.resize(5 << 8, (0, Duration::ZERO));
+        counts
+    });
+    let result = run(
         &mut mem,
         &mut rand,
         &text,
@@ -31,7 +53,9 @@ pub fn init<I: Interface>(data: Vec<u8>, config: Config, interface: &mut I) -> R
         &mut undo,
         &config,
         &mut err_said,
-    )
+    );
+    *counts = state.counts.take();
+    result
 }
 
 pub fn run<I: Interface>(
@@ -53,30 +77,207 @@ pub fn run<I: Interface>(
 ) -> Result<(), Error> {
     // or Result<!, Error>
     loop {
+        if !interface.running() {
+            return fatal(error(Cause::Stopped, (0, 0)));
+        }
+        if let Some(size) = interface.resized() {
+            fatal(header::resize(mem, size))?;
+            screen.resize(size.0);
+        }
         let addr = state.pc;
-        let instr = trace(fatal(instr::decode(mem, state, addr)), Trace::Decode(addr))?;
+        let instr = match instr::decode(mem, state, addr) {
+            Ok(instr) => instr,
+            Err(mut err) => {
+                err.calls = state.calls();
+                return trace(fatal(Err(err)), Trace::Decode(addr));
+            }
+        };
+        let start = state.counts.as_ref().map(|_| Instant::now());
+        let frame = state.frame();
         let result = opcode::exec(
             &instr, mem, rand, text, state, out, screen, input, header, obj, dict, interface,
             restart, undo, config, err_said,
         );
+        if let (Some(start), Some(counts)) = (start, &mut state.counts) {
+            let count = &mut counts[(instr.count as usize) << 8 | instr.opcode as usize];
+            count.0 += 1;
+            count.1 += start.elapsed();
+        }
 
-        if let Err(err) = trace(result, Trace::Exec(addr)) {
+        if let Err(mut err) = trace(result, Trace::Exec(addr)) {
+            if err.instr.is_none() {
+                err.instr = Some(Instruction {
+                    addr,
+                    name: opcode::name(mem[0], instr.count, instr.opcode),
+                    operands: instr.operands.to_vec(),
+                });
+                // the stack is only that of the instruction if it failed before changing frames
+                if state.frame() == frame {
+                    err.calls = state.calls();
+                }
+            }
             if err.fatal {
                 return Err(err);
-            } else {
-                match config.error {
-                    ErrorLevel::Never => {}
-                    ErrorLevel::Once => {
-                        let id = err.cause as usize;
-                        if !err_said[id] {
-                            interface.error(err);
-                            err_said[id] = true;
-                        }
-                    }
-                    ErrorLevel::Always => interface.error(err),
-                    ErrorLevel::Quit => return Err(err),
-                }
             }
+            report(err, interface, config, err_said)?;
         }
     }
 }
+
+/// Restart the game, reinitializing every subsystem as [`init_counting`] does
+///
+/// The transcript and fixed-pitch bits of Flags 2 survive the restart.
+pub fn restart<I: Interface>(
+    mem: &mut mem::Mem,
+    rand: &mut alu::Random,
+    state: &mut state::State,
+    out: &mut out::Output,
+    screen: &mut screen::Screen,
+    input: &mut input::Input,
+    interface: &mut I,
+    restart: &state::Saved,
+    config: &Config,
+) -> Result<(), Error> {
+    let flags = mem[0x11] & 0x03;
+    state.restore(mem, rand, restart);
+    *rand = alu::init(config);
+    header::init(mem, config)?;
+    mem.storeb(0x11, u16::from(mem[0x11] & !0x03 | flags))?;
+    *out = out::init();
+    *input = input::init(mem);
+    screen.reset(interface)
+}
+
+/// Report a non-fatal error as the [`ErrorPolicy`] says. Returns the error if the game must quit.
+pub fn report<I: Interface>(
+    err: Error,
+    interface: &mut I,
+    config: &Config,
+    err_said: &mut [bool],
+) -> Result<(), Error> {
+    match config.error.level(err.cause) {
+        ErrorLevel::Never => {}
+        ErrorLevel::Once => {
+            let id = err.cause as usize;
+            if !err_said[id] {
+                interface.error(err);
+                err_said[id] = true;
+            }
+        }
+        ErrorLevel::Always => interface.error(err),
+        ErrorLevel::Quit => return Err(err),
+    }
+    Ok(())
+}
+
+/// A version 5 story running `code`, with an empty dictionary and object table
+#[cfg(test)]
+pub fn story(code: &[u8]) -> Vec<u8> {
+    let mut data = vec![0; 0x400];
+    data[0x00] = 5;
+    for (addr, value) in [
+        (0x06, 0x400),
+        (0x08, 0x220),
+        (0x0a, 0x224),
+        (0x0c, 0x40),
+        (0x0e, 0x400),
+    ] {
+        data[addr..addr + 2].copy_from_slice(&u16::to_be_bytes(value));
+    }
+    data[0x221] = 9;
+    data.extend(code);
+    data
+}
+
+/// An interface recording the screen and the transcript, reading chars from a list, saving in memory
+#[cfg(test)]
+pub struct Test {
+    pub output: String,
+    pub transcript: String,
+    chars: std::vec::IntoIter<char>,
+    saved: Option<Vec<u8>>,
+}
+
+#[cfg(test)]
+impl Interface for Test {
+    fn write_screen(&mut self, text: &str, _: bool) {
+        self.output.push_str(text);
+    }
+    fn write_transcript(&mut self, text: &str) {
+        self.transcript.push_str(text);
+    }
+    fn window_font(&mut self, font: u16) -> bool {
+        matches!(font, 1 | 4)
+    }
+    fn read_char<T: FnMut(&mut Self) -> bool>(&mut self, _: u16, _: T) -> char {
+        self.chars.next().unwrap()
+    }
+    fn save(&mut self, data: &[u8]) -> bool {
+        self.saved = Some(data.to_vec());
+        true
+    }
+    fn restore(&mut self) -> Vec<u8> {
+        self.saved.take().unwrap_or_default()
+    }
+}
+
+/// Plays the `story`, reading the `chars`. Returns the output and the error ending the game.
+#[cfg(test)]
+pub fn play(story: Vec<u8>, chars: &str) -> (String, Error) {
+    let (test, err) = play_with(story, chars, interface::DEFAULT);
+    (test.output, err)
+}
+
+/// Plays the `story` like [`play`] with this `config`, returning the whole interface
+#[cfg(test)]
+pub fn play_with(story: Vec<u8>, chars: &str, config: Config) -> (Test, Error) {
+    let mut test = Test {
+        output: String::new(),
+        transcript: String::new(),
+        chars: chars.chars().collect::<Vec<_>>().into_iter(),
+        saved: None,
+    };
+    let err = init(story.into(), config, &mut test).unwrap_err();
+    (test, err)
+}
+
+#[test]
+fn test_outcome() {
+    // print_num 7, quit
+    let (output, err) = play(story(&[0xe6, 0x7f, 0x07, 0xba]), "");
+    assert_eq!((output.as_str(), err.cause), ("7", Cause::Quit));
+
+    // jump before the start of the memory
+    let (_, err) = play(story(&[0x8c, 0xf0, 0x00]), "");
+    assert_eq!((err.cause, err.fatal), (Cause::PcOut, true));
+}
+
+#[test]
+fn test_calls() {
+    // call_vs 0x408 -> sp, quit; 0x408: call_vs 0x410 -> g8, quit
+    let mut code = vec![0xe0, 0x3f, 0x01, 0x02, 0x00, 0xba, 0x00, 0x00];
+    code.extend(&[0x00, 0xe0, 0x3f, 0x01, 0x04, 0x18, 0xba, 0x00]);
+    let routine = |body| {
+        let mut data = story(&code);
+        data.extend(&[0x00, body]);
+        data
+    };
+    let (_, err) = play(routine(0xba), "");
+    let calls: Vec<_> = err.calls.iter().map(|call| call.routine).collect();
+    assert_eq!(
+        (err.cause, calls),
+        (Cause::Quit, vec![Some(0x410), Some(0x408)])
+    );
+
+    // the globals end at the static memory, so rtrue fails after leaving its routine
+    let mut data = routine(0xb0);
+    data[0x0c..0x0e].copy_from_slice(&u16::to_be_bytes(0x3f0));
+    let config = Config {
+        error: ErrorPolicy::new(ErrorLevel::Quit),
+        ..interface::DEFAULT
+    };
+    let (_, err) = play_with(data, "", config);
+    let name = err.instr.as_ref().map(|instr| instr.name);
+    assert_eq!((err.cause, name), (Cause::WriteOut, Some("rtrue")));
+    assert!(err.calls.is_empty());
+}
@@ -13,10 +13,11 @@ pub fn restore(init: &Saved, saved: &[u8], checksum: u16) -> Result<Saved, SaveE
     let mut stack = Err(MissingStks);
     let mut pc = None;
     let mut call = None;
+    let mut rand = None;
 
     let mut i = 12;
     while i < len {
-        let len = from_bytes(&saved.get(i + 4..i + 8).ok_or(BadSave)?);
+        let len = from_bytes(saved.get(i + 4..i + 8).ok_or(BadSave)?);
         let chunk = &saved.get(i..i + len + 8).ok_or(BadSave)?;
         i += len + 8;
         if len & 1 == 1 {
@@ -61,26 +62,36 @@ pub fn restore(init: &Saved, saved: &[u8], checksum: u16) -> Result<Saved, SaveE
                 let mut stack_ = Vec::new();
                 let mut call_ = Vec::new();
 
-                let stk_len = from_bytes(&chunk.get(14..16).ok_or(BadSave)?) * 2;
-                stack_.push(to_u16(&chunk.get(16..stk_len + 16).ok_or(BadSave)?));
+                let stk_len = from_bytes(chunk.get(14..16).ok_or(BadSave)?) * 2;
+                stack_.push(to_u16(chunk.get(16..stk_len + 16).ok_or(BadSave)?));
                 let mut i = stk_len + 16;
 
                 while i < len + 8 {
                     let var_len = (chunk[i + 3] as usize & 0x0f) * 2;
                     let stk_len = from_bytes(&chunk[i + 6..i + 8]) * 2;
                     call_.push(Frame {
+                        routine: None,
                         ret: from_bytes(&chunk[i..i + 3]),
-                        local: to_u16(&chunk.get(i + 8..i + 8 + var_len).ok_or(BadSave)?),
+                        local: to_u16(chunk.get(i + 8..i + 8 + var_len).ok_or(BadSave)?),
                         args: chunk[i + 5].count_ones() as u16,
                         store: chunk[i + 3] & 0x10 == 0,
                     });
                     i += 8 + var_len;
-                    stack_.push(to_u16(&chunk.get(i..i + stk_len).ok_or(BadSave)?));
+                    stack_.push(to_u16(chunk.get(i..i + stk_len).ok_or(BadSave)?));
                     i += stk_len;
                 }
                 stack = Ok(stack_);
                 call = Some(call_);
             }
+            b"Rand" => {
+                if len < 10 {
+                    return Err(BadSave);
+                }
+                rand = Some((
+                    u64::from_be_bytes(chunk[8..16].try_into().unwrap()),
+                    from_bytes(&chunk[16..18]) as u16,
+                ));
+            }
             _ => {}
         }
     }
@@ -89,6 +100,7 @@ pub fn restore(init: &Saved, saved: &[u8], checksum: u16) -> Result<Saved, SaveE
         stack: stack?,
         pc: pc.unwrap(),
         call: call.unwrap(),
+        rand,
     })
 }
 
@@ -161,6 +173,7 @@ fn test_restore() {
         stack: Vec::new(),
         pc: 10,
         call: Vec::new(),
+        rand: None,
     };
     let result = restore(&init, &save, 0x4321).unwrap();
 
@@ -172,18 +185,50 @@ fn test_restore() {
     );
     assert_eq!(result.pc, 10);
     assert_eq!(result.call.len(), 2);
+    assert_eq!(result.rand, None);
 
     let frame = &result.call[0];
     assert_eq!(frame.ret, 1);
     assert_eq!(frame.local, vec![3, 2, 1]);
     assert_eq!(frame.args, 2);
-    assert_eq!(frame.store, true);
+    assert!(frame.store);
 
     let frame = &result.call[1];
     assert_eq!(frame.ret, 16);
     assert_eq!(frame.local, vec![6, 5, 4]);
     assert_eq!(frame.args, 2);
-    assert_eq!(frame.store, false);
+    assert!(!frame.store);
+}
+
+#[test]
+fn test_rand() {
+    let mut save = Vec::new();
+    save.extend(b"FORM");
+    save.extend(vec![0, 0, 0, 70]);
+    save.extend(b"IFZSIFhd");
+    save.extend(vec![0, 0, 0, 13]);
+    save.extend(vec![0; 8]);
+    save.extend(vec![0x43, 0x21, 0, 0, 10, 0]);
+    save.extend(b"UMem");
+    save.extend(vec![0, 0, 0, 0x40]);
+    save.extend(vec![0; 0x40]);
+    save.extend(b"Stks");
+    save.extend(vec![0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0]);
+    save.extend(b"Rand");
+    save.extend(vec![0, 0, 0, 10]);
+    save.extend(vec![1, 2, 3, 4, 5, 6, 7, 8, 0, 9]);
+    let len = save.len() - 8;
+    save[7] = len as u8;
+
+    let init = Saved {
+        mem: mem::default(),
+        stack: Vec::new(),
+        pc: 10,
+        call: Vec::new(),
+        rand: None,
+    };
+    let result = restore(&init, &save, 0x4321).unwrap();
+    assert_eq!(result.rand, Some((0x0102_0304_0506_0708, 9)));
 }
 
 #[test]
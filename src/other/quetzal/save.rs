@@ -34,7 +34,7 @@ pub fn save(mem: &Mem, init: &Saved, saved: &Saved, checksum: u16) -> Vec<u8> {
         stacks.push(0x7f >> (7 - frame.1.args));
         stacks.extend_from_slice(&bytes(frame.0.len())[2..4]);
         extend_u16(&frame.1.local, &mut stacks);
-        extend_u16(&frame.0, &mut stacks);
+        extend_u16(frame.0, &mut stacks);
     }
 
     let len = stacks.len();
@@ -45,6 +45,12 @@ pub fn save(mem: &Mem, init: &Saved, saved: &Saved, checksum: u16) -> Vec<u8> {
         save.push(0);
     }
 
+    if let Some((state, count)) = saved.rand {
+        save.extend(b"Rand\0\0\0\x0a");
+        save.extend_from_slice(&state.to_be_bytes());
+        save.extend_from_slice(&count.to_be_bytes());
+    }
+
     let len = save.len() - 8;
     save[4..8].copy_from_slice(&bytes(len));
     save
@@ -101,6 +107,7 @@ fn test_save() {
         stack: Vec::new(),
         pc: 10,
         call: Vec::new(),
+        rand: None,
     };
     mem.storeb(0x3f, 1).unwrap();
     let mut saved = Saved {
@@ -108,17 +115,20 @@ fn test_save() {
         stack: Vec::new(),
         pc: 10,
         call: Vec::new(),
+        rand: None,
     };
     saved.stack.push(vec![1, 2, 3]);
     saved.stack.push(vec![4, 5, 6]);
     saved.stack.push(vec![7, 8, 9]);
     saved.call.push(Frame {
+        routine: None,
         ret: 1,
         local: vec![3, 2, 1],
         args: 2,
         store: true,
     });
     saved.call.push(Frame {
+        routine: None,
         ret: 16,
         local: vec![6, 5, 4],
         args: 2,
@@ -144,6 +154,12 @@ fn test_save() {
         0, 0, 16, 19, 0, 3, 0, 3, 0, 6, 0, 5, 0, 4, 0, 7, 0, 8, 0, 9,
     ]); // frame 2
     assert_eq!(save(&mem, &init, &saved, 0x4321), exepted);
+
+    saved.rand = Some((0x0102_0304_0506_0708, 9));
+    exepted[7] = 118;
+    exepted.extend(b"Rand");
+    exepted.extend(vec![0, 0, 0, 10, 1, 2, 3, 4, 5, 6, 7, 8, 0, 9]);
+    assert_eq!(save(&mem, &init, &saved, 0x4321), exepted);
 }
 
 #[test]
@@ -0,0 +1,197 @@
+use crate::{
+    err::*,
+    opcode::{BRANCH, CALL, END, STORE, TEXT},
+    *,
+};
+use std::collections::HashSet;
+
+/// Checks a storyfile for problems, without playing it
+///
+/// The file can be a storyfile or a Blorb file containing one.
+/// This verifies the checksum and the bounds of the tables,
+/// and decodes the code reachable from the start of the game
+/// (routines called through a variable aren't reached).\
+/// It returns the problems found, or the error preventing the storyfile from being played.
+pub fn validate(file: &[u8]) -> Result<Vec<Error>, Error> {
+    let mem = mem::new(blorb::story(file)?)?;
+    let header = header::read(&mem)?;
+    let text = text::init(&mem, &header)?;
+    let v = mem[0x00];
+    let mut errors = Vec::new();
+
+    // old storyfiles don't have a checksum
+    let checksum = mem.loadw(0x1c)?;
+    if mem.loadw(0x1a)? != 0 && checksum != header.checksum {
+        errors.push(err(Cause::BadChecksum, (checksum, header.checksum)));
+    }
+
+    for abbr in 0..text::abbreviations(&mem) {
+        if let Err(error) = text.abbr(&mem, abbr) {
+            let out = error.cause == Cause::AbbrOut;
+            errors.push(error);
+            if out {
+                break;
+            }
+        }
+    }
+    if let Err(error) = dict::init(&mem).count(&mem) {
+        errors.push(error);
+    }
+    let obj = obj::init(&mem);
+    match obj.count(&mem) {
+        Ok(count) => {
+            for n in 1..=count as u16 {
+                if let Err(error) = obj.check_props(&mem, &text, n) {
+                    errors.push(error);
+                }
+            }
+        }
+        Err(error) => errors.push(error),
+    }
+    if v >= 5 {
+        let addr = header.get_extension(&mem, 3)?;
+        let len = mem.get(addr as usize).map(|&len| len as usize);
+        if addr != 0 && len.is_none_or(|len| addr as usize + 1 + 2 * len > mem.len()) {
+            errors.push(err(Cause::UnicodeOut, (addr, 0)));
+        }
+        let addr = mem.loadw(0x2e)?;
+        if addr != 0
+            && !mem
+                .story()
+                .get(addr as usize..)
+                .is_some_and(|table| table.contains(&0))
+        {
+            errors.push(err(Cause::TerminatingOut, (addr, 0)));
+        }
+    }
+
+    code(&mem, &text, &mut errors);
+    Ok(errors)
+}
+
+/// What was found in the code
+pub struct Code {
+    /// the strings printed, with their address and whether they are inline
+    pub strings: Vec<(usize, bool, String)>,
+    /// the end of the last instruction
+    pub end: usize,
+}
+
+/// Decodes the code reachable from the start of the game
+pub fn code(mem: &mem::Mem, text: &text::Text, errors: &mut Vec<Error>) -> Code {
+    let v = mem[0x00];
+    let mut code = Code {
+        strings: Vec::new(),
+        end: 0,
+    };
+    let mut routines = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut todo = vec![(mem.loadw(0x06).unwrap() as usize, None)];
+    while let Some((addr, routine)) = todo.pop() {
+        if !seen.insert(addr) {
+            continue;
+        }
+        let instr::Instr {
+            count,
+            opcode,
+            operands,
+            end,
+        } = match instr::decode_static(mem, addr) {
+            Ok(instr) => instr,
+            Err(error) => {
+                errors.push(at(error, addr, routine));
+                continue;
+            }
+        };
+        let shape = match opcode::info(v, count, opcode) {
+            Some((_, shape)) => shape,
+            None => {
+                let error = err(Cause::BadOpcode, (count.into(), opcode.into()));
+                errors.push(at(error, addr, routine));
+                continue;
+            }
+        };
+        let constant = operands.first().copied().flatten();
+
+        let mut next = end;
+        if shape & TEXT != 0 {
+            match trace(text.decode(mem, end), Trace::String(end)) {
+                Ok((string, end)) => {
+                    code.strings.push((next, true, string));
+                    next = end;
+                }
+                Err(error) => {
+                    errors.push(at(error, addr, routine));
+                    continue;
+                }
+            }
+        }
+        let string = match (count, opcode, constant) {
+            (1, 0x07, Some(string)) => Some(mem.byte(string)),
+            (1, 0x0d, Some(string)) => Some(mem.packed(string, false)),
+            _ => None,
+        };
+        if let Some(string) = string {
+            match trace(text.decode(mem, string), Trace::String(string)) {
+                Ok((decoded, _)) => code.strings.push((string, false, decoded)),
+                Err(error) => errors.push(at(error, addr, routine)),
+            }
+        }
+        if let (1, 0x0c, Some(offset)) = (count, opcode, constant) {
+            todo.push((
+                (end as isize + offset as i16 as isize - 2) as usize,
+                routine,
+            ));
+        }
+        if let (true, Some(addr)) = (shape & CALL != 0, constant) {
+            let addr = mem.packed(addr, true);
+            if addr != 0 && routines.insert(addr) {
+                match rout::info(mem, addr) {
+                    Ok((_, start)) => todo.push((start, Some(addr))),
+                    Err(mut error) => {
+                        error.trace.push(Trace::Rout(addr));
+                        errors.push(error);
+                    }
+                }
+            }
+        }
+        if shape & STORE != 0 {
+            next += 1;
+        }
+        if shape & BRANCH != 0 {
+            let top = match mem.get(next) {
+                Some(&top) => top as usize,
+                None => {
+                    errors.push(at(err(Cause::PcOut, (0, 0)), addr, routine));
+                    continue;
+                }
+            };
+            let offset = if top & 0x40 == 0 {
+                next += 2;
+                let low = *mem.get(next - 1).unwrap_or(&0) as usize;
+                // signed 14 bits
+                (((top & 0x3f) << 8 | low) << 2) as u16 as i16 as isize >> 2
+            } else {
+                next += 1;
+                (top & 0x3f) as isize
+            };
+            if offset != 0 && offset != 1 {
+                todo.push(((next as isize + offset - 2) as usize, routine));
+            }
+        }
+        code.end = code.end.max(next);
+        if shape & END == 0 {
+            todo.push((next, routine));
+        }
+    }
+    code
+}
+
+/// Locates an error in the code
+fn at(mut error: Error, addr: usize, routine: Option<usize>) -> Error {
+    error.trace.push(Trace::Decode(addr));
+    if let Some(routine) = routine {
+        error.trace.push(Trace::Rout(routine));
+    }
+    error
+}
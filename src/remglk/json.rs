@@ -0,0 +1,214 @@
+use std::io::{BufRead, Bytes};
+
+/// A JSON value
+#[derive(Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+static NULL: Json = Json::Null;
+
+impl Json {
+    /// The field `key` of an object, or null
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map_or(&NULL, |(_, value)| value),
+            _ => &NULL,
+        }
+    }
+
+    pub fn str(&self) -> Option<&str> {
+        match self {
+            Json::String(str) => Some(str),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> Option<f64> {
+        match self {
+            Json::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+/// Reads JSON values one after another from a stream
+pub struct Reader<R: BufRead> {
+    bytes: Bytes<R>,
+    peeked: Option<u8>,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(read: R) -> Reader<R> {
+        Reader {
+            bytes: read.bytes(),
+            peeked: None,
+        }
+    }
+
+    /// The next value of the stream, or `None` at its end or on a syntax error
+    pub fn next(&mut self) -> Option<Json> {
+        self.skip();
+        self.value()
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        if self.peeked.is_none() {
+            self.peeked = self.bytes.next()?.ok();
+        }
+        self.peeked
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        let byte = self.peek();
+        self.peeked = None;
+        byte
+    }
+
+    fn skip(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.peeked = None;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip();
+        (self.byte()? == byte).then_some(())
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip();
+        Some(match self.peek()? {
+            b'{' => {
+                self.peeked = None;
+                let mut fields = Vec::new();
+                self.skip();
+                if self.peek()? == b'}' {
+                    self.peeked = None;
+                    return Some(Json::Object(fields));
+                }
+                loop {
+                    self.expect(b'"')?;
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value()?));
+                    self.skip();
+                    match self.byte()? {
+                        b',' => {}
+                        b'}' => break Json::Object(fields),
+                        _ => return None,
+                    }
+                }
+            }
+            b'[' => {
+                self.peeked = None;
+                let mut values = Vec::new();
+                self.skip();
+                if self.peek()? == b']' {
+                    self.peeked = None;
+                    return Some(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.skip();
+                    match self.byte()? {
+                        b',' => {}
+                        b']' => break Json::Array(values),
+                        _ => return None,
+                    }
+                }
+            }
+            b'"' => {
+                self.peeked = None;
+                Json::String(self.string()?)
+            }
+            b't' => self.literal("true", Json::Bool(true))?,
+            b'f' => self.literal("false", Json::Bool(false))?,
+            b'n' => self.literal("null", Json::Null)?,
+            _ => {
+                let mut number = String::new();
+                while let Some(byte @ (b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')) =
+                    self.peek()
+                {
+                    number.push(byte as char);
+                    self.peeked = None;
+                }
+                Json::Number(number.parse().ok()?)
+            }
+        })
+    }
+
+    fn literal(&mut self, name: &str, value: Json) -> Option<Json> {
+        for byte in name.bytes() {
+            if self.byte()? != byte {
+                return None;
+            }
+        }
+        Some(value)
+    }
+
+    /// Reads a string after its opening quote
+    fn string(&mut self) -> Option<String> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.byte()? {
+                b'"' => return String::from_utf8(bytes).ok(),
+                b'\\' => {
+                    let char = match self.byte()? {
+                        b'b' => '\x08',
+                        b'f' => '\x0c',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let high = self.hex()?;
+                            if (0xd800..0xdc00).contains(&high) {
+                                self.literal("\\u", Json::Null)?;
+                                let low =
+                                    self.hex()?.checked_sub(0xdc00).filter(|low| *low < 0x400)?;
+                                char::from_u32(0x10000 + ((high - 0xd800) << 10) + low)?
+                            } else {
+                                char::from_u32(high)?
+                            }
+                        }
+                        byte => byte as char,
+                    };
+                    bytes.extend(char.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => bytes.push(byte),
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..4 {
+            value = value * 16 + (self.byte()? as char).to_digit(16)?;
+        }
+        Some(value)
+    }
+}
+
+/// Writes `text` as a JSON string
+pub fn string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for char in text.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\0'..='\x1f' => json.push_str(&format!("\\u{:04x}", char as u32)),
+            _ => json.push(char),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,783 @@
+//! A frontend speaking the [RemGlk](https://eblong.com/zarf/glk/remglk/docs.html) JSON protocol on stdin and stdout, for web clients like GlkOte
+//!
+//! The lower window is a buffer window and the upper one (with the status line in V1-3) a grid window. Each update answers one input event.
+//! The files named by the client are kept in the current directory, with only the letters, digits, `-`, `_` and `.` of their names.
+
+use json::{string, Json, Reader};
+use rzif::{Error, *};
+use std::{
+    env::Args,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, StdinLock, Write},
+    process,
+};
+
+mod json;
+
+/// The id of the buffer window
+const BUFFER: u16 = 1;
+/// The id of the grid window
+const GRID: u16 = 2;
+
+pub fn main(name: &str, mut args: Args) {
+    let path = match args.next() {
+        Some(path) => path,
+        None => {
+            eprintln!(
+                "Usage: {} remglk path/to/storyfile [never|once|always|quit|pedantic|forgiving|frotz] [seed]",
+                name
+            );
+            process::exit(2);
+        }
+    };
+    let story = match fs::read(&path) {
+        Ok(story) => story,
+        Err(err) => {
+            eprintln!("Error opening the file {}: {}", path, err);
+            process::exit(2);
+        }
+    };
+    let level = args.next().unwrap_or_else(|| "always".to_string());
+    let error = crate::policy(&level).unwrap_or_else(|| {
+        eprintln!("invalid parameter: {}", level);
+        process::exit(2);
+    });
+    let seed = args.next().map(|seed| match seed.parse() {
+        Ok(seed) => seed,
+        Err(err) => {
+            eprintln!("invalid seed {}: {}", seed, err);
+            process::exit(2);
+        }
+    });
+    let patches = crate::patches().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(2);
+    });
+
+    let mut glk = match init(story[0]) {
+        Some(glk) => glk,
+        None => {
+            eprintln!("Expected an init event");
+            process::exit(2);
+        }
+    };
+    let config = Config {
+        status: true,
+        split: true,
+        fixed_default: false,
+        color: false,
+        bold: true,
+        italic: true,
+        fixed: true,
+        timed: true,
+        screen: glk.metrics.size(),
+        default_color: (2, 9),
+        true_color: (0x0000, 0x7fff),
+        picture: false,
+        graphics: true,
+        undo: true,
+        sound: false,
+        transparency: false,
+        patches: &patches,
+        error,
+        seed,
+        generator: Generator::Xorshift,
+    };
+
+    match rzif::main(story, config, &mut glk) {
+        Outcome::Quit | Outcome::Stopped => glk.update(&Request::Exit),
+        Outcome::Fatal(error) => {
+            glk.update(&Request::None);
+            println!(
+                "{{\"type\":\"error\",\"message\":{}}}",
+                string(&error.to_string())
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// The size of the screen, from the metrics of the client
+struct Metrics {
+    width: u32,
+    height: u32,
+    char_width: u32,
+    char_height: u32,
+}
+
+impl Metrics {
+    fn new(json: &Json) -> Metrics {
+        let get = |key, default| json.get(key).number().map_or(default, |n| n as u32).max(1);
+        Metrics {
+            width: get("width", 80),
+            height: get("height", 24),
+            char_width: get("gridcharwidth", 1),
+            char_height: get("gridcharheight", 1),
+        }
+    }
+
+    /// The (width; height) of the screen in characters
+    fn size(&self) -> (u16, u16) {
+        (
+            (self.width / self.char_width).min(0xff) as u16,
+            (self.height / self.char_height).min(0xff) as u16,
+        )
+    }
+}
+
+/// A line of a buffer window, continuing the previous one if `append`
+struct Paragraph {
+    append: bool,
+    runs: Vec<(&'static str, String)>,
+}
+
+/// What the update asks to the client
+enum Request<'a> {
+    None,
+    Line {
+        gen: u32,
+        initial: &'a str,
+        maxlen: u16,
+        terminators: &'a [char],
+    },
+    Char {
+        gen: u32,
+    },
+    File {
+        mode: &'static str,
+        kind: &'static str,
+    },
+    Exit,
+}
+
+pub struct Glk {
+    v: u8,
+    gen: u32,
+    events: Reader<StdinLock<'static>>,
+    metrics: Metrics,
+    closed: bool,
+    resized: bool,
+    /// The windows changed since the last update
+    arranged: bool,
+    current: u16,
+    style: u16,
+    /// The buffer window was cleared since the last update
+    clear: bool,
+    /// The text added to the buffer window since the last update
+    text: Vec<Paragraph>,
+    grid: Vec<Vec<(char, &'static str)>>,
+    /// The lines of the grid changed since the last update
+    dirty: Vec<bool>,
+    cursor: (u16, u16),
+    /// The timer, in tenths of seconds, and whether it changed since the last update
+    timer: (u16, bool),
+    transcript: Option<Box<dyn Write>>,
+    cmd_out: Option<Box<dyn Write>>,
+    cmd_in: Option<Box<dyn BufRead>>,
+}
+
+impl Glk {
+    /// The style of the text written now
+    fn style(&self, fixed: bool) -> &'static str {
+        if self.style & 0x01 != 0 {
+            "alert"
+        } else if self.style & 0x02 != 0 {
+            "subheader"
+        } else if self.style & 0x04 != 0 {
+            "emphasized"
+        } else if fixed || self.style & 0x08 != 0 {
+            "preformatted"
+        } else {
+            "normal"
+        }
+    }
+
+    fn write_buffer(&mut self, text: &str, style: &'static str) {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 || self.text.is_empty() {
+                self.text.push(Paragraph {
+                    append: i == 0,
+                    runs: Vec::new(),
+                });
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let runs = &mut self.text.last_mut().unwrap().runs;
+            match runs.last_mut() {
+                Some((last, text)) if *last == style => text.push_str(line),
+                _ => runs.push((style, line.to_string())),
+            }
+        }
+    }
+
+    /// The first line of the upper window in the grid, below the status line in V1-3
+    fn top(&self) -> u16 {
+        if self.v <= 3 {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn write_grid(&mut self, text: &str, style: &'static str) {
+        for char in text.chars() {
+            let (line, column) = (
+                (self.cursor.0 as usize).wrapping_sub(1),
+                (self.cursor.1 as usize).wrapping_sub(1),
+            );
+            if char == '\n' {
+                self.cursor = (self.cursor.0 + 1, 1);
+            } else if line < self.grid.len() && column < self.grid[line].len() {
+                self.grid[line][column] = (char, style);
+                self.dirty[line] = true;
+                self.cursor.1 += 1;
+            }
+        }
+    }
+
+    /// Set the height of the grid window in lines
+    fn grid_height(&mut self, height: u16) {
+        let width = self.metrics.size().0 as usize;
+        self.grid.resize(height as usize, Vec::new());
+        for line in self.grid.iter_mut() {
+            line.resize(width, (' ', "normal"));
+        }
+        self.dirty = vec![true; height as usize];
+        self.arranged = true;
+    }
+
+    fn clear_grid(&mut self, lines: std::ops::Range<usize>) {
+        for line in lines {
+            for cell in self.grid[line].iter_mut() {
+                *cell = (' ', "normal");
+            }
+            self.dirty[line] = true;
+        }
+    }
+
+    fn arrange(&mut self, metrics: &Json) {
+        self.metrics = Metrics::new(metrics);
+        self.grid_height(self.grid.len() as u16);
+        self.resized = true;
+    }
+
+    fn set_timer(&mut self, time: u16) {
+        if self.timer.0 != time {
+            self.timer = (time, true);
+        }
+    }
+
+    /// Write an update to stdout, with the changes since the last one
+    fn update(&mut self, request: &Request) {
+        self.gen += 1;
+        let mut json = format!("{{\"type\":\"update\",\"gen\":{}", self.gen);
+        if self.arranged {
+            let top = self.grid.len() as u32 * self.metrics.char_height;
+            json.push_str(&format!(
+                ",\"windows\":[{{\"id\":{},\"type\":\"buffer\",\"rock\":0,\"left\":0,\"top\":{},\"width\":{},\"height\":{}}}",
+                BUFFER,
+                top,
+                self.metrics.width,
+                self.metrics.height.saturating_sub(top)
+            ));
+            if !self.grid.is_empty() {
+                json.push_str(&format!(
+                    ",{{\"id\":{},\"type\":\"grid\",\"rock\":0,\"gridwidth\":{},\"gridheight\":{},\"left\":0,\"top\":0,\"width\":{},\"height\":{}}}",
+                    GRID,
+                    self.metrics.size().0,
+                    self.grid.len(),
+                    self.metrics.width,
+                    top
+                ));
+            }
+            json.push(']');
+            self.arranged = false;
+        }
+
+        let mut content = Vec::new();
+        if self.clear
+            || self
+                .text
+                .iter()
+                .any(|par| !par.append || !par.runs.is_empty())
+        {
+            let mut window = format!("{{\"id\":{}", BUFFER);
+            if self.clear {
+                window.push_str(",\"clear\":true");
+            }
+            let text = self
+                .text
+                .drain(..)
+                .filter(|par| !par.append || !par.runs.is_empty())
+                .map(|par| match (par.append, par.runs.is_empty()) {
+                    (false, true) => "{}".to_string(),
+                    (append, _) => format!(
+                        "{{{}\"content\":{}}}",
+                        if append { "\"append\":true," } else { "" },
+                        runs(par.runs.iter().map(|(style, text)| (*style, text.as_str())))
+                    ),
+                })
+                .collect::<Vec<_>>();
+            window.push_str(&format!(",\"text\":[{}]}}", text.join(",")));
+            content.push(window);
+        }
+        self.clear = false;
+        self.text.clear();
+        if self.dirty.contains(&true) {
+            let mut lines = Vec::new();
+            for (i, line) in self.grid.iter().enumerate() {
+                if !self.dirty[i] {
+                    continue;
+                }
+                let mut cells = Vec::<(&str, String)>::new();
+                for &(char, style) in line.iter() {
+                    match cells.last_mut() {
+                        Some((last, text)) if *last == style => text.push(char),
+                        _ => cells.push((style, char.to_string())),
+                    }
+                }
+                let cells = cells.iter().map(|(style, text)| (*style, text.as_str()));
+                lines.push(format!("{{\"line\":{},\"content\":{}}}", i, runs(cells)));
+            }
+            content.push(format!(
+                "{{\"id\":{},\"lines\":[{}]}}",
+                GRID,
+                lines.join(",")
+            ));
+            self.dirty = vec![false; self.grid.len()];
+        }
+        if !content.is_empty() {
+            json.push_str(&format!(",\"content\":[{}]", content.join(",")));
+        }
+
+        match request {
+            Request::Line {
+                gen,
+                initial,
+                maxlen,
+                terminators,
+            } => {
+                json.push_str(&format!(
+                    ",\"input\":[{{\"id\":{},\"gen\":{},\"type\":\"line\",\"maxlen\":{}",
+                    BUFFER, gen, maxlen
+                ));
+                if !initial.is_empty() {
+                    json.push_str(&format!(",\"initial\":{}", string(initial)));
+                }
+                let terminators = terminators
+                    .iter()
+                    .filter_map(|&char| key_name(char))
+                    .map(string)
+                    .collect::<Vec<_>>();
+                if !terminators.is_empty() {
+                    json.push_str(&format!(",\"terminators\":[{}]", terminators.join(",")));
+                }
+                json.push_str("}]");
+            }
+            Request::Char { gen } => json.push_str(&format!(
+                ",\"input\":[{{\"id\":{},\"gen\":{},\"type\":\"char\"}}]",
+                BUFFER, gen
+            )),
+            _ => json.push_str(",\"input\":[]"),
+        }
+        if self.timer.1 {
+            if self.timer.0 == 0 {
+                json.push_str(",\"timer\":null");
+            } else {
+                json.push_str(&format!(",\"timer\":{}", self.timer.0 as u32 * 100));
+            }
+            self.timer.1 = false;
+        }
+        match request {
+            Request::File { mode, kind } => json.push_str(&format!(
+                ",\"specialinput\":{{\"type\":\"fileref_prompt\",\"filemode\":\"{}\",\"filetype\":\"{}\"}}",
+                mode, kind
+            )),
+            Request::Exit => json.push_str(",\"exit\":true"),
+            _ => {}
+        }
+        json.push('}');
+
+        // the client is gone if its updates can't be written
+        let mut stdout = io::stdout().lock();
+        if writeln!(stdout, "{}", json)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            self.closed = true;
+        }
+    }
+
+    /// The next event of the client for the current generation, or `None` at the end of the input
+    fn event(&mut self) -> Option<Json> {
+        loop {
+            let event = match self.events.next() {
+                Some(event) => event,
+                None => {
+                    self.closed = true;
+                    return None;
+                }
+            };
+            // events sent before the last update are outdated
+            if event.get("gen").number() == Some(self.gen as f64) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Ask the player for a file to open in `mode` ("read" or "write"), in the current directory
+    fn prompt(&mut self, mode: &'static str, kind: &'static str) -> Option<String> {
+        if self.closed {
+            return None;
+        }
+        self.update(&Request::File { mode, kind });
+        let event = self.event()?;
+        if event.get("type").str() != Some("specialresponse") {
+            return None;
+        }
+        event.get("value").str().and_then(path)
+    }
+
+    fn file_out(&mut self, kind: &'static str) -> Box<dyn Write> {
+        match self
+            .prompt("write", kind)
+            .and_then(|name| File::create(name).ok())
+        {
+            Some(file) => Box::new(BufWriter::new(file)),
+            None => Box::new(io::sink()),
+        }
+    }
+}
+
+/// The runs of styled text of a line
+fn runs<'a>(runs: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    let runs = runs
+        .map(|(style, text)| format!("{{\"style\":\"{}\",\"text\":{}}}", style, string(text)))
+        .collect::<Vec<_>>();
+    format!("[{}]", runs.join(","))
+}
+
+/// The Glk name of a special key
+fn key_name(char: char) -> Option<&'static str> {
+    const FUNCTIONS: [&str; 12] = [
+        "func1", "func2", "func3", "func4", "func5", "func6", "func7", "func8", "func9", "func10",
+        "func11", "func12",
+    ];
+    Some(match char as u32 {
+        8 => "delete",
+        10 => "return",
+        27 => "escape",
+        129 => "up",
+        130 => "down",
+        131 => "left",
+        132 => "right",
+        n @ 133..=144 => FUNCTIONS[n as usize - 133],
+        _ => return None,
+    })
+}
+
+/// The char of a key given by the client, by its Glk name or as itself
+fn key(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        return Some(char);
+    }
+    (1..=144)
+        .filter_map(char::from_u32)
+        .find(|&char| key_name(char) == Some(value))
+}
+
+impl Interface for Glk {
+    fn write_screen(&mut self, text: &str, fixed: bool) {
+        let style = self.style(fixed);
+        if self.current == 1 {
+            self.write_grid(text, style);
+        } else {
+            self.write_buffer(text, style);
+        }
+    }
+
+    fn write_transcript(&mut self, text: &str) {
+        if self.transcript.is_none() {
+            self.transcript = Some(self.file_out("transcript"));
+        }
+        let _ = self.transcript.as_mut().unwrap().write_all(text.as_bytes());
+    }
+
+    fn write_command(&mut self, text: &str) {
+        if self.cmd_out.is_none() {
+            self.cmd_out = Some(self.file_out("command"));
+        }
+        let _ = self.cmd_out.as_mut().unwrap().write_all(text.as_bytes());
+    }
+
+    fn status(&mut self, text: &str) {
+        if self.grid.is_empty() {
+            return;
+        }
+        let cursor = self.cursor;
+        self.cursor = (1, 1);
+        self.clear_grid(0..1);
+        self.write_grid(text, "normal");
+        self.cursor = cursor;
+    }
+
+    fn window_font(&mut self, font: u16) -> bool {
+        matches!(font, 1 | 4)
+    }
+
+    fn window_color(&mut self, _: u16, _: u16) {}
+
+    fn window_style(&mut self, style: u16) {
+        if style == 0 {
+            self.style = 0;
+        } else {
+            self.style |= style;
+        }
+    }
+
+    fn window_set(&mut self, window: u16) {
+        self.current = window;
+        if window == 1 {
+            self.cursor = (self.top() + 1, 1);
+        }
+    }
+
+    fn window_buffer(&mut self, _: u16) {}
+
+    fn window_split(&mut self, lines: u16) {
+        self.grid_height(self.top() + lines);
+        if self.cursor.0 as usize > self.grid.len() {
+            self.cursor = (self.top() + 1, 1);
+        }
+    }
+
+    fn window_cursor_set(&mut self, line: u16, column: u16) {
+        if self.current == 1 {
+            self.cursor = (self.top() + line, column);
+        }
+    }
+
+    fn window_cursor_get(&mut self) -> (u16, u16) {
+        (self.cursor.0.saturating_sub(self.top()), self.cursor.1)
+    }
+
+    fn window_erase(&mut self, window: u16) {
+        if matches!(window, 0 | 0xfffe | 0xffff) {
+            self.clear = true;
+            self.text.clear();
+        }
+        if matches!(window, 1 | 0xfffe | 0xffff) {
+            self.clear_grid(self.top() as usize..self.grid.len());
+            self.cursor = (self.top() + 1, 1);
+        }
+        if window == 0xffff {
+            self.window_split(0);
+            self.current = 0;
+        }
+    }
+
+    fn window_line(&mut self) {
+        if self.current != 1 {
+            return;
+        }
+        let (line, column) = (
+            (self.cursor.0 as usize).wrapping_sub(1),
+            (self.cursor.1 as usize).wrapping_sub(1),
+        );
+        if let Some(cells) = self.grid.get_mut(line) {
+            for cell in cells.iter_mut().skip(column) {
+                *cell = (' ', "normal");
+            }
+            self.dirty[line] = true;
+        }
+    }
+
+    fn read<T: FnMut(&mut Self, &str) -> bool>(
+        &mut self,
+        terminators: Vec<char>,
+        initial: String,
+        maxlen: u16,
+        time: u16,
+        mut routine: T,
+    ) -> (String, char) {
+        let request = Request::Line {
+            gen: self.gen + 1,
+            initial: &initial,
+            maxlen,
+            terminators: &terminators,
+        };
+        self.set_timer(time);
+        loop {
+            if self.closed {
+                return (String::new(), '\n');
+            }
+            self.update(&request);
+            let event = match self.event() {
+                Some(event) => event,
+                None => return (String::new(), '\n'),
+            };
+            match event.get("type").str() {
+                Some("line") => {
+                    let value = event.get("value").str().unwrap_or_default().to_string();
+                    let char = match event.get("terminator").str().and_then(key) {
+                        Some(char) if terminators.contains(&char) => char,
+                        _ => '\n',
+                    };
+                    self.write_buffer(&value, "input");
+                    self.set_timer(0);
+                    return (value, char);
+                }
+                // the client sends the text typed so far, which stays in its input line
+                Some("timer") => {
+                    let partial = event.get("partial").get(&BUFFER.to_string());
+                    let partial = partial.str().unwrap_or_default().to_string();
+                    if routine(self, &partial) {
+                        self.set_timer(0);
+                        return (partial, '\0');
+                    }
+                }
+                Some("arrange") => self.arrange(event.get("metrics")),
+                _ => {}
+            }
+        }
+    }
+
+    fn read_char<T: FnMut(&mut Self) -> bool>(&mut self, time: u16, mut routine: T) -> char {
+        let request = Request::Char { gen: self.gen + 1 };
+        self.set_timer(time);
+        loop {
+            if self.closed {
+                return '\n';
+            }
+            self.update(&request);
+            let event = match self.event() {
+                Some(event) => event,
+                None => return '\n',
+            };
+            match event.get("type").str() {
+                Some("char") => {
+                    if let Some(char) = event.get("value").str().and_then(key) {
+                        self.set_timer(0);
+                        return char;
+                    }
+                }
+                Some("timer") if routine(self) => {
+                    self.set_timer(0);
+                    return '\0';
+                }
+                Some("arrange") => self.arrange(event.get("metrics")),
+                _ => {}
+            }
+        }
+    }
+
+    fn read_file(&mut self) -> String {
+        if self.cmd_in.is_none() {
+            let file = self
+                .prompt("read", "command")
+                .and_then(|name| File::open(name).ok());
+            self.cmd_in = Some(match file {
+                Some(file) => Box::new(BufReader::new(file)),
+                None => Box::new(io::empty()),
+            });
+        }
+        let mut line = String::new();
+        let _ = self.cmd_in.as_mut().unwrap().read_line(&mut line);
+        line
+    }
+
+    fn bleep(&mut self, _: u16) {}
+
+    fn save(&mut self, data: &[u8]) -> bool {
+        match self.prompt("write", "save") {
+            Some(name) => fs::write(name, data).is_ok(),
+            None => false,
+        }
+    }
+
+    fn restore(&mut self) -> Vec<u8> {
+        self.prompt("read", "save")
+            .and_then(|name| fs::read(name).ok())
+            .unwrap_or_default()
+    }
+
+    fn restore_failed(&mut self, cause: SaveError) {
+        if let SaveError::GamesDiffer = cause {
+            self.write_buffer("This file wasn't saved from this game\n", "alert");
+        } else {
+            self.write_buffer(&format!("Corrupted save file: {}\n", cause), "alert");
+        }
+    }
+
+    fn save_aux(&mut self, name: &str, data: &[u8]) -> bool {
+        if name.is_empty() {
+            return self.save(data);
+        }
+        path(name).is_some_and(|name| fs::write(name, data).is_ok())
+    }
+
+    fn restore_aux(&mut self, name: &str) -> Option<Vec<u8>> {
+        let name = if name.is_empty() {
+            self.prompt("read", "data")?
+        } else {
+            path(name)?
+        };
+        fs::read(name).ok()
+    }
+
+    fn error(&mut self, error: Error) {
+        eprintln!("{}", error);
+    }
+
+    fn running(&mut self) -> bool {
+        !self.closed
+    }
+
+    fn resized(&mut self) -> Option<(u16, u16)> {
+        if !self.resized {
+            return None;
+        }
+        self.resized = false;
+        Some(self.metrics.size())
+    }
+}
+
+/// The file `name` in the current directory, if it has a name left once sanitized
+fn path(name: &str) -> Option<String> {
+    Some(crate::file_name(name)).filter(|name| !name.is_empty())
+}
+
+/// Wait for the init event of the client
+fn init(v: u8) -> Option<Glk> {
+    let mut events = Reader::new(io::stdin().lock());
+    let event = events.next()?;
+    if event.get("type").str() != Some("init") {
+        return None;
+    }
+    let mut glk = Glk {
+        v,
+        gen: event.get("gen").number().unwrap_or(0.0) as u32,
+        events,
+        metrics: Metrics::new(event.get("metrics")),
+        closed: false,
+        resized: false,
+        arranged: true,
+        current: 0,
+        style: 0,
+        clear: false,
+        text: Vec::new(),
+        grid: Vec::new(),
+        dirty: Vec::new(),
+        cursor: (1, 1),
+        timer: (0, false),
+        transcript: None,
+        cmd_out: None,
+        cmd_in: None,
+    };
+    if v <= 3 {
+        glk.grid_height(1);
+    }
+    Some(glk)
+}
@@ -0,0 +1,64 @@
+use std::{env::Args, fs, process};
+
+pub fn main(name: &str, mut args: Args) {
+    let (story, walkthrough, seed) = match (args.next(), args.next(), args.next()) {
+        (Some(story), Some(walkthrough), Some(seed)) => (story, walkthrough, seed),
+        _ => {
+            eprintln!(
+                "Usage: {} replay path/to/storyfile path/to/walkthrough seed [path/to/golden]",
+                name
+            );
+            process::exit(2);
+        }
+    };
+    let story = read(&story);
+    let walkthrough = String::from_utf8_lossy(&read(&walkthrough)).into_owned();
+    let seed = match seed.parse() {
+        Ok(seed) => seed,
+        Err(err) => {
+            eprintln!("invalid seed {}: {}", seed, err);
+            process::exit(2);
+        }
+    };
+
+    let mut transcript = rzif::replay(story, &walkthrough, seed);
+    let end = transcript.errors.pop();
+    for error in transcript.errors.iter() {
+        eprintln!("{}", error);
+    }
+    if let Some(error) = end {
+        if error.fatal && error.cause != rzif::Cause::Quit && error.cause != rzif::Cause::Stopped {
+            eprintln!("{}", error);
+        }
+    }
+
+    match args.next() {
+        Some(golden) => {
+            let golden = String::from_utf8_lossy(&read(&golden)).into_owned();
+            match transcript.diff(&golden) {
+                None => println!("The transcript matches the golden file"),
+                Some(diff) => {
+                    print!("The transcript diverges at turn {}", diff.turn);
+                    if let Some(command) = diff.command {
+                        print!(" (\"{}\")", command);
+                    }
+                    println!(", line {}:", diff.line);
+                    println!("  expected: {}", diff.expected);
+                    println!("  found:    {}", diff.found);
+                    process::exit(1);
+                }
+            }
+        }
+        None => print!("{}", transcript),
+    }
+}
+
+fn read(path: &str) -> Vec<u8> {
+    match fs::read(path) {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("Error opening the file {}: {}", path, err);
+            process::exit(2);
+        }
+    }
+}
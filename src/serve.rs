@@ -0,0 +1,485 @@
+//! A server playing a game with each telnet client, in line mode
+//!
+//! Each session has its own game, sharing the storyfile and its decoded instructions with the others.
+//! With a directory of saves, the player is asked for a name and their files are kept in its subdirectory.
+//! The name isn't authenticated: anyone giving the same name shares the same files, so don't use it for anything private.
+
+use rzif::{Error, *};
+use std::{
+    env::Args,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Write},
+    mem,
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The longest line a player can send, in bytes
+const MAX_LINE: usize = 4096;
+
+pub fn main(name: &str, mut args: Args) {
+    let (story, address) = match (args.next(), args.next()) {
+        (Some(story), Some(address)) => (story, address),
+        _ => {
+            eprintln!(
+                "Usage: {} serve path/to/storyfile address:port [path/to/saves] [idle seconds] [max sessions]",
+                name
+            );
+            process::exit(2);
+        }
+    };
+    let story = match fs::read(&story) {
+        Ok(story) => story,
+        Err(err) => {
+            eprintln!("Error opening the file {}: {}", story, err);
+            process::exit(2);
+        }
+    };
+    // shared by all the sessions
+    let story = match rzif::storyfile(story) {
+        Ok(story) => story,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    };
+    let saves = args.next().map(PathBuf::from);
+    let idle = match args.next().map(|idle| idle.parse()) {
+        None => Duration::from_secs(600),
+        Some(Ok(idle)) => Duration::from_secs(idle),
+        Some(Err(err)) => {
+            eprintln!("invalid idle timeout: {}", err);
+            process::exit(2);
+        }
+    };
+    let max = match args.next().map(|max| max.parse()) {
+        None => 100,
+        Some(Ok(max)) => max,
+        Some(Err(err)) => {
+            eprintln!("invalid maximum of sessions: {}", err);
+            process::exit(2);
+        }
+    };
+    let patches: Arc<[Patch]> = crate::patches()
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        })
+        .into();
+
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Error listening on {}: {}", address, err);
+            process::exit(2);
+        }
+    };
+    println!("Listening on {}", listener.local_addr().unwrap());
+    io::stdout().flush().unwrap();
+    let sessions = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Error accepting a connection: {}", err);
+                continue;
+            }
+        };
+        if sessions.load(Ordering::Relaxed) >= max {
+            let _ = stream.write_all(b"Too many players, try again later\r\n");
+            continue;
+        }
+        sessions.fetch_add(1, Ordering::Relaxed);
+        let story = story.clone();
+        let saves = saves.clone();
+        let patches = patches.clone();
+        let sessions = sessions.clone();
+        thread::spawn(move || {
+            play(story, stream, saves, idle, patches);
+            sessions.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+/// Plays a game on a connection, in its own session
+fn play(
+    story: Story,
+    stream: TcpStream,
+    saves: Option<PathBuf>,
+    idle: Duration,
+    patches: Arc<[Patch]>,
+) {
+    let mut session = match Session::new(stream, idle) {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("Error opening a session: {}", err);
+            return;
+        }
+    };
+    if let Some(saves) = saves {
+        session.write("Your name: ");
+        if let Line::Text(name) = session.line(0, |_| false) {
+            let name = crate::file_name(&name);
+            if !name.is_empty() && fs::create_dir_all(saves.join(&name)).is_ok() {
+                session.dir = Some(saves.join(name));
+            }
+        }
+    }
+
+    let config = Config {
+        status: true,
+        split: false,
+        fixed_default: true,
+        color: false,
+        bold: false,
+        italic: false,
+        fixed: true,
+        timed: true,
+        screen: (80, 24),
+        default_color: (2, 9),
+        true_color: (0x0000, 0x7fff),
+        picture: false,
+        graphics: true,
+        undo: true,
+        sound: false,
+        transparency: false,
+        patches: &patches,
+        error: ErrorPolicy::new(ErrorLevel::Always),
+        seed: None,
+        generator: Generator::Xorshift,
+    };
+    match rzif::main(story, config, &mut session) {
+        Outcome::Quit => {}
+        Outcome::Stopped => {
+            if session.timed_out {
+                session.write("\nIdle for too long, goodbye\n");
+            }
+        }
+        Outcome::Fatal(error) => session.write(&format!("\n{}\n", error)),
+    }
+    let _ = session.output.flush();
+}
+
+/// A line of the player
+enum Line {
+    Text(String),
+    /// The timed routine stopped the input
+    Interrupted,
+    /// The connection was closed, or the player was idle for too long
+    Closed,
+}
+
+/// A game on a connection, with line-mode input
+struct Session {
+    input: BufReader<TcpStream>,
+    output: BufWriter<TcpStream>,
+    idle: Duration,
+    /// When the player sent the last line
+    last: Instant,
+    /// The bytes of the line being received, up to `MAX_LINE`
+    pending: Vec<u8>,
+    closed: bool,
+    timed_out: bool,
+    /// The player ended the last line with enter, so the next newline of the game is already on the screen
+    newline: bool,
+    window: u16,
+    status: String,
+    /// The directory of the files of the player, if any
+    dir: Option<PathBuf>,
+    transcript: Option<Box<dyn Write + Send>>,
+    cmd_out: Option<Box<dyn Write + Send>>,
+    cmd_in: Option<Box<dyn BufRead + Send>>,
+}
+
+impl Session {
+    fn new(stream: TcpStream, idle: Duration) -> io::Result<Session> {
+        // a player not reading the output is idle too (a zero timeout is refused)
+        stream.set_write_timeout(Some(idle.max(Duration::from_millis(1))))?;
+        Ok(Session {
+            input: BufReader::new(stream.try_clone()?),
+            output: BufWriter::new(stream),
+            idle,
+            last: Instant::now(),
+            pending: Vec::new(),
+            closed: false,
+            timed_out: false,
+            newline: false,
+            window: 0,
+            status: String::new(),
+            dir: None,
+            transcript: None,
+            cmd_out: None,
+            cmd_in: None,
+        })
+    }
+
+    /// Write `text` to the player, with telnet newlines
+    fn write(&mut self, mut text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if mem::take(&mut self.newline) {
+            text = text.strip_prefix('\n').unwrap_or(text);
+        }
+        if self
+            .output
+            .write_all(text.replace('\n', "\r\n").as_bytes())
+            .is_err()
+        {
+            self.closed = true;
+        }
+    }
+
+    /// Read a line of the player, calling `tick` every `time` tenths of seconds (if not 0) until it returns true
+    fn line<F: FnMut(&mut Self) -> bool>(&mut self, time: u16, mut tick: F) -> Line {
+        let interval = Duration::from_millis(u64::from(time) * 100);
+        let mut last_tick = Instant::now();
+        loop {
+            if self.closed || self.output.flush().is_err() {
+                self.closed = true;
+                return Line::Closed;
+            }
+            let mut wait = self.idle.saturating_sub(self.last.elapsed());
+            if time != 0 {
+                wait = wait.min(interval.saturating_sub(last_tick.elapsed()));
+            }
+            // a zero timeout is refused
+            let wait = wait.max(Duration::from_millis(1));
+            let _ = self.input.get_ref().set_read_timeout(Some(wait));
+            let limit = (MAX_LINE - self.pending.len()) as u64;
+            match (&mut self.input)
+                .take(limit)
+                .read_until(b'\n', &mut self.pending)
+            {
+                Ok(0) => self.closed = true,
+                // a line too long to be typed
+                Ok(_) if self.pending.len() >= MAX_LINE && !self.pending.ends_with(b"\n") => {
+                    self.closed = true
+                }
+                Ok(_) => {
+                    self.last = Instant::now();
+                    return Line::Text(text(&mem::take(&mut self.pending)));
+                }
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    if self.last.elapsed() >= self.idle {
+                        self.closed = true;
+                        self.timed_out = true;
+                    } else if time != 0 && last_tick.elapsed() >= interval {
+                        if tick(self) {
+                            return Line::Interrupted;
+                        }
+                        last_tick = Instant::now();
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(_) => self.closed = true,
+            }
+        }
+    }
+
+    /// Ask the player for the name of a file in their directory
+    fn file(&mut self) -> Option<PathBuf> {
+        let dir = self.dir.clone()?;
+        self.write("\nEnter file name: ");
+        match self.line(0, |_| false) {
+            Line::Text(name) => {
+                let name = crate::file_name(&name);
+                Some(dir.join(if name.is_empty() { "save" } else { &name }))
+            }
+            _ => None,
+        }
+    }
+
+    fn file_out(&self, name: &str) -> Box<dyn Write + Send> {
+        match self
+            .dir
+            .as_ref()
+            .and_then(|dir| File::create(dir.join(name)).ok())
+        {
+            Some(file) => Box::new(BufWriter::new(file)),
+            None => Box::new(io::sink()),
+        }
+    }
+}
+
+/// The text of a line received from a telnet client, without its negotiations
+fn text(bytes: &[u8]) -> String {
+    let mut text = Vec::new();
+    let mut bytes = bytes.iter();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            // IAC, followed by a command
+            0xff => match bytes.next() {
+                Some(0xff) => text.push(0xff),
+                Some(0xfb..=0xfe) => {
+                    bytes.next();
+                }
+                Some(0xfa) => while !matches!(bytes.next(), Some(0xf0) | None) {},
+                _ => {}
+            },
+            b'\r' | b'\n' | 0 => {}
+            _ => text.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&text).into_owned()
+}
+
+impl Interface for Session {
+    fn write_screen(&mut self, text: &str, _: bool) {
+        if self.window == 0 {
+            self.write(text);
+        }
+    }
+
+    fn write_transcript(&mut self, text: &str) {
+        if self.transcript.is_none() {
+            self.transcript = Some(self.file_out("transcript.txt"));
+        }
+        let _ = self.transcript.as_mut().unwrap().write_all(text.as_bytes());
+    }
+
+    fn write_command(&mut self, text: &str) {
+        if self.cmd_out.is_none() {
+            self.cmd_out = Some(self.file_out("commands.rec"));
+        }
+        let _ = self.cmd_out.as_mut().unwrap().write_all(text.as_bytes());
+    }
+
+    fn status(&mut self, text: &str) {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text != self.status {
+            self.write(&format!("[{}]\n", text));
+            self.status = text;
+        }
+    }
+
+    fn window_font(&mut self, font: u16) -> bool {
+        matches!(font, 1 | 4)
+    }
+
+    fn window_color(&mut self, _: u16, _: u16) {}
+
+    fn window_style(&mut self, _: u16) {}
+
+    fn window_set(&mut self, window: u16) {
+        self.window = window;
+    }
+
+    fn window_buffer(&mut self, _: u16) {}
+
+    fn window_split(&mut self, _: u16) {}
+
+    fn window_cursor_set(&mut self, _: u16, _: u16) {}
+
+    fn window_cursor_get(&mut self) -> (u16, u16) {
+        (1, 1)
+    }
+
+    fn window_erase(&mut self, _: u16) {}
+
+    fn window_line(&mut self) {}
+
+    fn read<T: FnMut(&mut Self, &str) -> bool>(
+        &mut self,
+        _: Vec<char>,
+        preload: String,
+        _: u16,
+        time: u16,
+        mut routine: T,
+    ) -> (String, char) {
+        // the text typed so far stays in the line of the client, which sends it only with enter:
+        // the timed routine is told that nothing was typed, so the interpreter can't redraw it
+        match self.line(time, |session| routine(session, "")) {
+            Line::Text(line) => {
+                self.newline = true;
+                (preload + &line, '\n')
+            }
+            Line::Interrupted => (preload, '\0'),
+            Line::Closed => (preload, '\n'),
+        }
+    }
+
+    fn read_char<T: FnMut(&mut Self) -> bool>(&mut self, time: u16, routine: T) -> char {
+        match self.line(time, routine) {
+            Line::Text(line) => {
+                self.newline = true;
+                line.chars().next().unwrap_or('\n')
+            }
+            Line::Interrupted => '\0',
+            Line::Closed => '\n',
+        }
+    }
+
+    fn read_file(&mut self) -> String {
+        if self.cmd_in.is_none() {
+            let file = self
+                .dir
+                .as_ref()
+                .and_then(|dir| File::open(dir.join("commands.rec")).ok());
+            self.cmd_in = Some(match file {
+                Some(file) => Box::new(BufReader::new(file)),
+                None => Box::new(io::empty()),
+            });
+        }
+        let mut line = String::new();
+        let _ = self.cmd_in.as_mut().unwrap().read_line(&mut line);
+        line
+    }
+
+    fn bleep(&mut self, _: u16) {
+        self.write("\x07");
+    }
+
+    fn save(&mut self, data: &[u8]) -> bool {
+        match self.file() {
+            Some(path) => fs::write(path, data).is_ok(),
+            None => false,
+        }
+    }
+
+    fn restore(&mut self) -> Vec<u8> {
+        self.file()
+            .and_then(|path| fs::read(path).ok())
+            .unwrap_or_default()
+    }
+
+    fn restore_failed(&mut self, cause: SaveError) {
+        if let SaveError::GamesDiffer = cause {
+            self.write("This file wasn't saved from this game\n");
+        } else {
+            self.write(&format!("Corrupted save file: {}\n", cause));
+        }
+    }
+
+    fn save_aux(&mut self, name: &str, data: &[u8]) -> bool {
+        if name.is_empty() {
+            return self.save(data);
+        }
+        match &self.dir {
+            Some(dir) => fs::write(dir.join(crate::file_name(name)), data).is_ok(),
+            None => false,
+        }
+    }
+
+    fn restore_aux(&mut self, name: &str) -> Option<Vec<u8>> {
+        if name.is_empty() {
+            return fs::read(self.file()?).ok();
+        }
+        fs::read(self.dir.as_ref()?.join(crate::file_name(name))).ok()
+    }
+
+    fn error(&mut self, error: Error) {
+        self.write(&format!("\n{}\n", error));
+    }
+
+    fn running(&mut self) -> bool {
+        !self.closed
+    }
+}
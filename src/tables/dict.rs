@@ -124,12 +124,13 @@ impl Dict {
             }
         } else {
             for &(ref word, addr, len) in words.iter() {
+                // search in [min, max), max never reaching past the last entry
                 let mut min = 0;
                 let mut max = count;
                 if !flag {
                     mem.storew(parse, 0)?;
                 }
-                while min <= max {
+                while min < max {
                     let nbr = (min + max) / 2;
                     let addr = dict + nbr * size;
                     let entry = if self.v <= 3 {
@@ -138,13 +139,7 @@ impl Dict {
                         vec![mem.loadw(addr)?, mem.loadw(addr + 2)?, mem.loadw(addr + 4)?]
                     };
                     match word.cmp(&entry) {
-                        Ordering::Less => {
-                            if nbr == 0 {
-                                break;
-                            } else {
-                                max = nbr - 1;
-                            }
-                        }
+                        Ordering::Less => max = nbr,
                         Ordering::Equal => {
                             mem.storew(parse, addr)?;
                             break;
@@ -161,6 +156,24 @@ impl Dict {
         }
         Ok(())
     }
+
+    /// The number of words in the dictionary
+    pub fn count(&self, mem: &Mem) -> Result<usize, Error> {
+        Ok(self.words(mem)?.len())
+    }
+
+    /// The addresses of the words in the dictionary
+    pub fn words(&self, mem: &Mem) -> Result<Vec<usize>, Error> {
+        let out = || err(Cause::DictOut, (self.addr, 0));
+        let addr = self.addr as usize;
+        let addr = addr + 1 + *mem.get(addr).ok_or_else(out)? as usize;
+        let size = *mem.get(addr).ok_or_else(out)? as usize;
+        let count = (mem.getw(addr + 1).ok_or_else(out)? as i16).unsigned_abs() as usize;
+        if addr + 3 + size * count > mem.len() {
+            return error(Cause::DictOut, (self.addr, 0));
+        }
+        Ok((0..count).map(|i| addr + 3 + size * i).collect())
+    }
 }
 
 pub fn init(mem: &Mem) -> Dict {
@@ -195,6 +208,7 @@ fn test_old() {
     let dict = init(&mem);
     dict.parse(&mut mem, &text, 0x5e, 0x40, None, false)
         .unwrap();
+    assert_eq!(dict.count(&mem).unwrap(), 4);
 
     assert_eq!(
         mem.save()[0x40..0x5e],
@@ -53,20 +53,29 @@ impl Header {
     }
 }
 
-pub fn init(mem: &mut Mem, config: &Config) -> Result<Header, Error> {
-    let v = mem[0];
+/// Reads the header, without changing it
+pub fn read(mem: &Mem) -> Result<Header, Error> {
     let mut checksum = 0u16;
-    let len = match v {
+    let len = match mem[0] {
         1..=3 => 2,
         4..=5 => 4,
         7..=8 => 8,
         _ => unreachable!(),
     } * mem.loadw(0x1a).unwrap() as usize;
-    for &m in mem.iter().take(len).skip(0x40) {
+    for &m in mem.story().iter().take(len).skip(0x40) {
         checksum = checksum.wrapping_add(u16::from(m));
     }
     let ext_addr = mem.loadw(0x36).unwrap();
     let ext_len = mem.loadw(ext_addr)?;
+    Ok(Header {
+        checksum,
+        extension: (ext_addr, ext_len),
+    })
+}
+
+pub fn init(mem: &mut Mem, config: &Config) -> Result<Header, Error> {
+    let v = mem[0];
+    let header = read(mem)?;
 
     if v <= 3 {
         let mut flags = mem.loadb(0x01).unwrap() & 0x8f;
@@ -100,21 +109,28 @@ pub fn init(mem: &mut Mem, config: &Config) -> Result<Header, Error> {
         mem.storeb(0x01, flags)?;
     }
 
-    let mut flags = mem.loadb(0x11).unwrap();
-    flags &= if v >= 5 && !config.picture {
-        0x57
+    // the game asks for features in Flags 2, the bits of the missing ones are cleared (there is no mouse nor menus)
+    let mut flags = mem.loadb(0x11).unwrap() & !0x20;
+    if v < 5 {
+        flags &= !0x80;
     } else {
-        0x5f
-    };
+        for (bit, available) in [
+            (0x08, config.picture),
+            (0x10, config.undo),
+            (0x40, config.color),
+            (0x80, config.sound),
+        ] {
+            if !available {
+                flags &= !bit;
+            }
+        }
+    }
     mem.storeb(0x11, flags)?;
+    let menus = mem.loadb(0x10).unwrap() & !0x01;
+    mem.storeb(0x10, menus)?;
     mem.storeb(0x1e, 0x01)?;
-    if v >= 4 {
-        mem.storeb(0x20, config.screen.1)?;
-        mem.storeb(0x21, config.screen.0)?;
-    }
+    resize(mem, config.screen)?;
     if v >= 5 {
-        mem.storew(0x22, config.screen.0)?;
-        mem.storew(0x24, config.screen.1)?;
         mem.storeb(0x26, 0x01)?;
         mem.storeb(0x27, 0x01)?;
     }
@@ -122,16 +138,28 @@ pub fn init(mem: &mut Mem, config: &Config) -> Result<Header, Error> {
     mem.storeb(0x2d, config.default_color.0.into())?;
     mem.storew(0x32, 0x0101)?;
 
-    let header = Header {
-        checksum,
-        extension: (ext_addr, ext_len),
-    };
-    header.set_extension(mem, 4, 0)?;
+    let transparency = header.get_extension(mem, 4)? & 0x01;
+    header.set_extension(mem, 4, if config.transparency { transparency } else { 0 })?;
     header.set_extension(mem, 5, config.true_color.0)?;
     header.set_extension(mem, 6, config.true_color.1)?;
     Ok(header)
 }
 
+/// Write the `width` and `height` of the screen, in characters, to the header
+pub fn resize(mem: &mut Mem, (width, height): (u16, u16)) -> Result<(), Error> {
+    let v = mem[0];
+    if v >= 4 {
+        // in chars, 255 meaning infinite
+        mem.storeb(0x20, height.min(255))?;
+        mem.storeb(0x21, width.min(255))?;
+    }
+    if v >= 5 {
+        mem.storew(0x22, width)?;
+        mem.storew(0x24, height)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 pub fn init_test(mem: &mut Mem) -> Header {
     init(mem, &interface::DEFAULT).unwrap()
@@ -155,6 +183,59 @@ fn test_checked() {
     assert_eq!(mem[0x11], 0x55);
 }
 
+#[test]
+fn test_resize() {
+    let mut data = mem::default();
+    data[0x00] = 5;
+    let mut mem = mem::new(data).unwrap();
+    init_test(&mut mem);
+    resize(&mut mem, (100, 30)).unwrap();
+    assert_eq!((mem[0x20], mem[0x21]), (30, 100));
+    assert_eq!(
+        (mem.loadw(0x22).unwrap(), mem.loadw(0x24).unwrap()),
+        (100, 30)
+    );
+    resize(&mut mem, (300, 256)).unwrap();
+    assert_eq!((mem[0x20], mem[0x21]), (255, 255));
+    assert_eq!(
+        (mem.loadw(0x22).unwrap(), mem.loadw(0x24).unwrap()),
+        (300, 256)
+    );
+
+    let mut mem = mem::new(mem::default()).unwrap();
+    resize(&mut mem, (40, 10)).unwrap();
+    assert_eq!((mem[0x20], mem[0x21]), (0, 0));
+}
+
+#[test]
+fn test_capabilities() {
+    let mut data = mem::default();
+    data[0x00] = 5;
+    data[0x0f] = 0x50;
+    data[0x10] = 0x01;
+    data[0x11] = 0xfb;
+    data[0x37] = 0x40;
+    data.extend(vec![0x00, 0x04, 0, 0, 0, 0, 0, 0, 0xff, 0xff]);
+    data.resize(0x50, 0);
+    let mut mem = mem::new(data.clone()).unwrap();
+    let header = init(&mut mem, &interface::DEFAULT).unwrap();
+    assert_eq!((mem[0x10], mem[0x11]), (0x00, 0x53));
+    assert_eq!(header.get_extension(&mem, 4).unwrap(), 0x0000);
+
+    let mut mem = mem::new(data).unwrap();
+    let config = Config {
+        picture: true,
+        undo: false,
+        color: false,
+        sound: true,
+        transparency: true,
+        ..interface::DEFAULT
+    };
+    let header = init(&mut mem, &config).unwrap();
+    assert_eq!(mem[0x11], 0x8b);
+    assert_eq!(header.get_extension(&mem, 4).unwrap(), 0x0001);
+}
+
 #[test]
 fn test_extension() {
     let mut data = mem::default();
@@ -21,25 +21,11 @@ impl Object {
     }
 
     fn addr(&self, obj: u16, attr: u16) -> Result<u16, Error> {
-        let base;
-        if obj == 0 {
-            return error(Cause::BadObj, (obj, 0));
+        let entry = self.entry(obj)?;
+        if attr >= if self.v123 { 32 } else { 48 } {
+            return error(Cause::BadAttr, (attr, 0));
         }
-        if self.v123 {
-            if obj > 255 {
-                return error(Cause::BadObj, (obj, 0));
-            }
-            if attr >= 32 {
-                return error(Cause::BadAttr, (attr, 0));
-            }
-            base = 53 + 9 * obj;
-        } else {
-            if attr >= 48 {
-                return error(Cause::BadAttr, (attr, 0));
-            }
-            base = 112 + 14 * obj;
-        }
-        Ok(base + self.addr + (attr >> 3))
+        Ok(entry + (attr >> 3))
     }
 }
 
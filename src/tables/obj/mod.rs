@@ -9,6 +9,49 @@ mod attr;
 mod prop;
 mod tree;
 
+impl Object {
+    /// The number of objects, assuming the first property table follows the last object
+    pub fn count(&self, mem: &Mem) -> Result<usize, Error> {
+        let (defaults, size, max) = if self.v123 {
+            (62, 9, 255)
+        } else {
+            (126, 14, 0xffff)
+        };
+        let start = self.addr as usize + defaults;
+        if start > mem.len() {
+            return error(Cause::ObjectsOut, (self.addr, 0));
+        }
+        let mut end = mem.len();
+        let mut count = 0;
+        while count < max && start + size * (count + 1) <= end {
+            let entry = start + size * count;
+            match mem.getw(entry + size - 2) {
+                Some(prop) if prop as usize >= entry + size => end = end.min(prop as usize),
+                _ => break,
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// The address of the entry of an object, which must be in the first 64K
+    fn entry(&self, obj: u16) -> Result<u16, Error> {
+        let (defaults, size, max) = if self.v123 {
+            (62, 9, 255)
+        } else {
+            (126, 14, 0xffff)
+        };
+        if obj == 0 || obj > max {
+            return error(Cause::BadObj, (obj, 0));
+        }
+        let entry = self.addr as usize + defaults + size * (obj as usize - 1);
+        if entry + size > 0x10000 {
+            return error(Cause::BadObj, (obj, 0));
+        }
+        Ok(entry as u16)
+    }
+}
+
 pub fn init(mem: &Mem) -> Object {
     Object {
         v123: mem[0] < 4,
@@ -25,11 +68,19 @@ fn test_init() {
     data[0x0a] = 1;
     let mem = mem::new(data.clone()).unwrap();
     let obj = init(&mem);
-    assert_eq!(obj.v123, true);
+    assert!(obj.v123);
     assert_eq!(obj.addr, 0x100);
     data[0x00] = 4;
     let mem = mem::new(data).unwrap();
     let obj = init(&mem);
-    assert_eq!(obj.v123, false);
+    assert!(!obj.v123);
     assert_eq!(obj.addr, 0x100);
+    assert_eq!(obj.entry(1).unwrap(), 0x17e);
+    assert_eq!(obj.entry(4653).unwrap(), 0xffe6);
+    // entries must end before 64K
+    assert_eq!(obj.entry(4654).unwrap_err().cause, Cause::BadObj);
+    assert_eq!(
+        obj.get_parent(&mem, 0xffff).unwrap_err().cause,
+        Cause::BadObj
+    );
 }
@@ -1,61 +1,70 @@
 use super::*;
 use crate::text::Text;
 
+/// `addr + offset`, which must stay in the first 64K
+fn add(addr: u16, offset: u16) -> Result<u16, Error> {
+    match addr.checked_add(offset) {
+        Some(addr) => Ok(addr),
+        None => error(Cause::ReadOut, (addr, 0)),
+    }
+}
+
 impl Object {
     pub fn prop_addr(&self, mem: &Mem, obj: u16, prop: u16) -> Result<u16, Error> {
-        if obj == 0 {
-            return error(Cause::BadObj, (obj, 0));
-        }
         if self.v123 {
-            if obj > 255 {
-                return error(Cause::BadObj, (obj, 0));
-            }
             self.prop_addr_old(mem, obj, prop)
         } else {
             self.prop_addr_new(mem, obj, prop)
         }
     }
 
+    /// The address of the pointer to the property table of an object
+    fn props(&self, obj: u16) -> Result<u16, Error> {
+        add(self.entry(obj)?, if self.v123 { 7 } else { 12 })
+    }
+
     fn prop_addr_old(&self, mem: &Mem, obj: u16, prop: u16) -> Result<u16, Error> {
+        let props = self.props(obj)?;
         if prop == 0 || prop > 31 {
             return error(Cause::BadProp, (prop, 0));
         }
-        let mut table = mem.loadw(self.addr + 60 + 9 * obj)?;
-        table += 2 * mem.loadb(table)? + 1;
+        let mut table = mem.loadw(props)?;
+        table = add(table, 2 * mem.loadb(table)? + 1)?;
         let mut info = mem.loadb(table)?;
         while prop <= info & 0x1f {
             if info & 0x1f == prop {
-                return Ok(table + 1);
+                return add(table, 1);
             }
-            table += (info >> 5) + 2;
+            table = add(table, (info >> 5) + 2)?;
             info = mem.loadb(table)?;
         }
         Ok(0)
     }
 
     fn prop_addr_new(&self, mem: &Mem, obj: u16, prop: u16) -> Result<u16, Error> {
+        let props = self.props(obj)?;
         if prop == 0 || prop > 63 {
             return error(Cause::BadProp, (prop, 0));
         }
-        let mut table = mem.loadw(self.addr + 124 + 14 * obj)?;
-        table += 2 * mem.loadb(table)? + 1;
+        let mut table = mem.loadw(props)?;
+        table = add(table, 2 * mem.loadb(table)? + 1)?;
         let mut info = mem.loadb(table)?;
         while prop <= info & 0x3f {
             if info & 0x3f == prop {
-                return Ok(table + if info & 0x80 == 0 { 1 } else { 2 });
+                return add(table, if info & 0x80 == 0 { 1 } else { 2 });
             }
-            match info & 0xc0 {
-                0x00 => table += 2,
-                0x40 => table += 3,
+            table = match info & 0xc0 {
+                0x00 => add(table, 2)?,
+                0x40 => add(table, 3)?,
                 _ => {
-                    let size = mem.loadb(table + 1)? & 0x3f;
+                    let size = mem.loadb(add(table, 1)?)? & 0x3f;
                     if size == 0 {
-                        table += 66;
+                        add(table, 66)?
                     } else {
-                        table += size + 2;
+                        add(table, size + 2)?
                     }
                 }
-            }
+            };
             info = mem.loadb(table)?;
         }
         Ok(0)
@@ -84,28 +93,22 @@ impl Object {
     }
 
     pub fn next_prop(&self, mem: &Mem, obj: u16, prop: u16) -> Result<u16, Error> {
-        if obj == 0 {
-            return error(Cause::BadObj, (obj, 0));
-        }
         if self.v123 {
-            if obj > 255 {
-                return error(Cause::BadObj, (obj, 0));
-            }
             let addr = if prop == 0 {
-                let addr = mem.loadw(self.addr + 60 + 9 * obj)?;
-                addr + 2 * mem.loadb(addr)? + 1
+                let addr = mem.loadw(self.props(obj)?)?;
+                add(addr, 2 * mem.loadb(addr)? + 1)?
             } else {
                 let addr = self.prop_addr_old(mem, obj, prop)?;
                 if addr == 0 {
                     return error(Cause::NoProp, (obj, prop));
                 }
-                addr + (mem.loadb(addr - 1)? >> 5) + 1
+                add(addr, (mem.loadb(addr - 1)? >> 5) + 1)?
             };
             Ok(mem.loadb(addr)? & 0x1f)
         } else {
             let addr = if prop == 0 {
-                let addr = mem.loadw(self.addr + 124 + 14 * obj)?;
-                addr + 2 * mem.loadb(addr)? + 1
+                let addr = mem.loadw(self.props(obj)?)?;
+                add(addr, 2 * mem.loadb(addr)? + 1)?
             } else {
                 let addr = self.prop_addr_new(mem, obj, prop)?;
                 if addr == 0 {
@@ -123,23 +126,17 @@ impl Object {
                         }
                     }
                 };
-                addr + size
+                add(addr, size)?
             };
             Ok(mem.loadb(addr)? & 0x3f)
         }
     }
 
     pub fn get_prop(&self, mem: &Mem, obj: u16, prop: u16) -> Result<u16, Error> {
-        if obj == 0 {
-            return error(Cause::BadObj, (obj, 0));
-        }
         if self.v123 {
-            if obj > 255 {
-                return error(Cause::BadObj, (obj, 0));
-            }
             let addr = self.prop_addr_old(mem, obj, prop)?;
             if addr == 0 {
-                Ok(mem.loadw(self.addr + prop * 2 - 2)?)
+                mem.loadw(add(self.addr, prop * 2 - 2)?)
             } else {
                 match mem.loadb(addr - 1)? >> 5 {
                     0 => mem.loadb(addr),
@@ -150,7 +147,7 @@ impl Object {
         } else {
             let addr = self.prop_addr_new(mem, obj, prop)?;
             if addr == 0 {
-                Ok(mem.loadw(self.addr + prop * 2 - 2)?)
+                mem.loadw(add(self.addr, prop * 2 - 2)?)
             } else {
                 match mem.loadb(addr - 1)? & 0xc0 {
                     0x00 => mem.loadb(addr),
@@ -162,13 +159,7 @@ impl Object {
     }
 
     pub fn put_prop(&self, mem: &mut Mem, obj: u16, prop: u16, data: u16) -> Result<(), Error> {
-        if obj == 0 {
-            return error(Cause::BadObj, (obj, 0));
-        }
         if self.v123 {
-            if obj > 255 {
-                return error(Cause::BadObj, (obj, 0));
-            }
             let addr = self.prop_addr_old(mem, obj, prop)?;
             if addr == 0 {
                 return error(Cause::NoProp, (obj, prop));
@@ -192,19 +183,44 @@ impl Object {
     }
 
     pub fn name(&self, mem: &Mem, text: &Text, obj: u16) -> Result<String, Error> {
-        if obj == 0 {
-            return error(Cause::BadObj, (obj, 0));
+        let addr = self.name_addr(mem, obj)?;
+        // a name of length 0 is empty, the properties follow directly
+        if mem.loadb((addr - 1) as u16)? == 0 {
+            return Ok(String::new());
         }
-        let addr = if self.v123 {
-            if obj > 255 {
-                return error(Cause::BadObj, (obj, 0));
-            }
-            mem.loadw(self.addr + 60 + 9 * obj)? as usize + 1
-        } else {
-            mem.loadw(self.addr + 124 + 14 * obj)? as usize + 1
-        };
         Ok(trace(text.decode(mem, addr), Trace::String(addr))?.0)
     }
+
+    /// The address of the short name of an object
+    pub fn name_addr(&self, mem: &Mem, obj: u16) -> Result<usize, Error> {
+        Ok(mem.loadw(self.props(obj)?)? as usize + 1)
+    }
+
+    /// Checks that the name and the properties of an object are readable
+    pub fn check_props(&self, mem: &Mem, text: &Text, obj: u16) -> Result<(), Error> {
+        self.name(mem, text, obj)?;
+        let out = || err(Cause::PropOut, (obj, 0));
+        let addr = mem.loadw(self.props(obj)?)? as usize;
+        let mut addr = addr + 1 + 2 * *mem.get(addr).ok_or_else(out)? as usize;
+        loop {
+            let size = *mem.get(addr).ok_or_else(out)?;
+            let (head, len) = if size == 0 {
+                return Ok(());
+            } else if self.v123 {
+                (1, (size >> 5) + 1)
+            } else {
+                match size & 0xc0 {
+                    0x00 => (1, 1),
+                    0x40 => (1, 2),
+                    _ => match *mem.get(addr + 1).ok_or_else(out)? & 0x3f {
+                        0 => (2, 64),
+                        len => (2, len),
+                    },
+                }
+            };
+            addr += head + len as usize;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -335,6 +351,10 @@ fn test_name_old() {
     let text = text::init(&mem, &header).unwrap();
     let obj = init(&mem);
     assert_eq!(obj.name(&mem, &text, 1).unwrap(), "Test");
+    assert_eq!(
+        obj.check_props(&mem, &text, 1).unwrap_err().cause,
+        Cause::PropOut
+    );
 }
 
 #[test]
@@ -349,4 +369,27 @@ fn test_name_new() {
     let text = text::init(&mem, &header).unwrap();
     let obj = init(&mem);
     assert_eq!(obj.name(&mem, &text, 1).unwrap(), "Test");
+    assert_eq!(
+        obj.check_props(&mem, &text, 1).unwrap_err().cause,
+        Cause::PropOut
+    );
+}
+
+#[test]
+fn test_addr_overflow() {
+    let mut data = mem::default();
+    data.resize(0x10000, 0);
+    data[0x00] = 0x04;
+    data[0x0b] = 0x40;
+    // the property table of the object 1 is at the end of the memory, with a long name
+    data[0xca] = 0xff;
+    data[0xcb] = 0xfe;
+    data[0xfffe] = 0xff;
+
+    let mem = mem::new(data).unwrap();
+    let obj = init(&mem);
+    for result in [obj.prop_addr(&mem, 1, 1), obj.next_prop(&mem, 1, 0)] {
+        let err = result.unwrap_err();
+        assert_eq!((err.cause, err.data), (Cause::ReadOut, (0xfffe, 0)));
+    }
 }
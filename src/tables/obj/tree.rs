@@ -2,43 +2,42 @@ use super::*;
 
 impl Object {
     pub fn get_parent(&self, mem: &Mem, obj: u16) -> Result<u16, Error> {
-        self.check(obj)?;
+        let entry = self.entry(obj)?;
         if self.v123 {
-            mem.loadb(self.addr + 57 + 9 * obj)
+            mem.loadb(entry + 4)
         } else {
-            mem.loadw(self.addr + 118 + 14 * obj)
+            mem.loadw(entry + 6)
         }
     }
 
     pub fn get_sibling(&self, mem: &Mem, obj: u16) -> Result<u16, Error> {
-        self.check(obj)?;
+        let entry = self.entry(obj)?;
         if self.v123 {
-            mem.loadb(self.addr + 58 + 9 * obj)
+            mem.loadb(entry + 5)
         } else {
-            mem.loadw(self.addr + 120 + 14 * obj)
+            mem.loadw(entry + 8)
         }
     }
 
     pub fn get_child(&self, mem: &Mem, obj: u16) -> Result<u16, Error> {
-        self.check(obj)?;
+        let entry = self.entry(obj)?;
         if self.v123 {
-            mem.loadb(self.addr + 59 + 9 * obj)
+            mem.loadb(entry + 6)
         } else {
-            mem.loadw(self.addr + 122 + 14 * obj)
+            mem.loadw(entry + 10)
         }
     }
 
     pub fn insert(&self, mem: &mut Mem, obj: u16, dest: u16) -> Result<(), Error> {
-        self.check(obj)?;
-        self.check(dest)?;
+        let addr = self.entry(obj)?;
+        let dest_addr = self.entry(dest)?;
         if self.v123 {
-            let base = self.addr + 58;
-            let addr = base + 9 * obj;
+            let addr = addr + 5;
             let parent = mem.loadb(addr - 1)?;
             let next = mem.loadb(addr)?;
 
             if parent != 0 {
-                let parent_addr = base + 1 + 9 * parent;
+                let parent_addr = self.entry(parent)? + 6;
                 let mut prev = mem.loadb(parent_addr)?;
                 if prev == obj {
                     mem.storeb(parent_addr, next)?;
@@ -49,7 +48,7 @@ impl Object {
                         if prev == 0 || count > 255 {
                             return error(Cause::NotChildOfParent, (obj, parent));
                         }
-                        prev_addr = base + 9 * prev;
+                        prev_addr = self.entry(prev)? + 5;
                         prev = mem.loadb(prev_addr)?;
                         count += 1;
                     }
@@ -57,19 +56,18 @@ impl Object {
                 }
             }
 
-            let dest_addr = base + 1 + 9 * dest;
+            let dest_addr = dest_addr + 6;
             let child = mem.loadb(dest_addr)?;
             mem.storeb(addr - 1, dest)?;
             mem.storeb(addr, child)?;
             mem.storeb(dest_addr, obj)?;
         } else {
-            let base = self.addr + 120;
-            let addr = base + 14 * obj;
+            let addr = addr + 8;
             let parent = mem.loadw(addr - 2)?;
             let next = mem.loadw(addr)?;
 
             if parent != 0 {
-                let parent_addr = base + 2 + 14 * parent; // ERROR multiply with overflow
+                let parent_addr = self.entry(parent)? + 10;
                 let mut prev = mem.loadw(parent_addr)?;
                 if prev == obj {
                     mem.storew(parent_addr, next)?;
@@ -80,7 +78,7 @@ impl Object {
                         if prev == 0 || count > 65535 {
                             return error(Cause::NotChildOfParent, (obj, parent));
                         }
-                        prev_addr = base + 14 * prev;
+                        prev_addr = self.entry(prev)? + 8;
                         prev = mem.loadw(prev_addr)?;
                         count += 1;
                     }
@@ -88,7 +86,7 @@ impl Object {
                 }
             }
 
-            let dest_addr = base + 2 + 14 * dest;
+            let dest_addr = dest_addr + 10;
             let child = mem.loadw(dest_addr)?;
             mem.storew(addr - 2, dest)?;
             mem.storew(addr, child)?;
@@ -98,16 +96,15 @@ impl Object {
     }
 
     pub fn remove(&self, mem: &mut Mem, obj: u16) -> Result<(), Error> {
-        self.check(obj)?;
+        let entry = self.entry(obj)?;
         if self.v123 {
-            let base = self.addr + 58;
-            let mut addr = base - 1 + 9 * obj;
+            let mut addr = entry + 4;
             let parent = mem.loadb(addr)?;
             if parent == 0 {
                 return Ok(());
             }
             mem.storeb(addr, 0)?;
-            let parent_addr = base + 1 + 9 * parent;
+            let parent_addr = self.entry(parent)? + 6;
             addr += 1;
             let next = mem.loadb(addr)?;
             mem.storeb(addr, 0)?;
@@ -121,21 +118,20 @@ impl Object {
                     if prev == 0 || count > 255 {
                         return error(Cause::NotChildOfParent, (obj, parent));
                     }
-                    prev_addr = base + 9 * prev;
+                    prev_addr = self.entry(prev)? + 5;
                     prev = mem.loadb(prev_addr)?;
                     count += 1;
                 }
                 mem.storeb(prev_addr, next)?;
             }
         } else {
-            let base = self.addr + 120;
-            let mut addr = base - 2 + 14 * obj;
+            let mut addr = entry + 6;
             let parent = mem.loadw(addr)?;
             if parent == 0 {
                 return Ok(());
             }
             mem.storew(addr, 0)?;
-            let parent_addr = base + 2 + 14 * parent;
+            let parent_addr = self.entry(parent)? + 10;
             addr += 2;
             let next = mem.loadw(addr)?;
             mem.storew(addr, 0)?;
@@ -149,7 +145,7 @@ impl Object {
                     if prev == 0 || count > 65535 {
                         return error(Cause::NotChildOfParent, (obj, parent));
                     }
-                    prev_addr = base + 14 * prev;
+                    prev_addr = self.entry(prev)? + 8;
                     prev = mem.loadw(prev_addr)?;
                     count += 1;
                 }
@@ -158,16 +154,6 @@ impl Object {
         }
         Ok(())
     }
-
-    fn check(&self, obj: u16) -> Result<(), Error> {
-        if obj == 0 {
-            return error(Cause::BadObj, (obj, 0));
-        }
-        if self.v123 && obj > 255 {
-            return error(Cause::BadObj, (obj, 0));
-        }
-        Ok(())
-    }
 }
 
 #[test]
@@ -48,7 +48,7 @@ pub fn exec<I: Interface>(
             }
             0x04 => state.pc = end,
             0x05 if v < 5 => {
-                let save = state.save(mem)?;
+                let save = state.save(mem, rand)?;
                 let result = interface.save(&quetzal::save(mem, restart, &save, header.checksum));
                 if v < 4 {
                     instr.branch(mem, state, result, false)?;
@@ -61,11 +61,11 @@ pub fn exec<I: Interface>(
                 let save = quetzal::restore(restart, &interface.restore(), header.checksum);
                 match save {
                     Ok(save) => {
-                        state.restore(mem, &save);
+                        state.restore(mem, rand, &save);
                         let instr = instr::Instr {
                             count: 0,
                             opcode: 0,
-                            operands: Vec::new(),
+                            operands: instr::Operands::default(),
                             end: state.pc,
                         };
                         if v < 4 {
@@ -87,9 +87,11 @@ pub fn exec<I: Interface>(
                     }
                 }
             }
-            0x07 => state.restore(mem, restart),
+            0x07 => machine::restart(
+                mem, rand, state, out, screen, input, interface, restart, config,
+            )?,
             0x08 => {
-                let val = handle(state.get_var(mem, 0), || state.ret(mem, 0))?;
+                let val = handle(state.get_var(mem, 0), |_| state.ret(mem, 0))?;
                 state.ret(mem, val)?;
             }
             0x09 => {
@@ -124,27 +126,33 @@ pub fn exec<I: Interface>(
                 instr.branch(mem, state, operands[0] == 0, false)?;
             }
             0x01 => {
-                let obj = handle(obj.get_sibling(mem, operands[0]), || {
-                    instr.branch(mem, state, false, true)
+                let obj = handle(obj.get_sibling(mem, operands[0]), |err| {
+                    instr.branch(mem, state, false, true)?;
+                    instr.store(mem, state, config.error.fallback(err.cause))
                 })?;
                 instr.branch(mem, state, obj != 0, true)?;
                 instr.store(mem, state, obj)?;
             }
             0x02 => {
-                let obj = handle(obj.get_child(mem, operands[0]), || {
-                    instr.branch(mem, state, false, true)
+                let obj = handle(obj.get_child(mem, operands[0]), |err| {
+                    instr.branch(mem, state, false, true)?;
+                    instr.store(mem, state, config.error.fallback(err.cause))
                 })?;
                 instr.branch(mem, state, obj != 0, true)?;
                 instr.store(mem, state, obj)?;
             }
             0x03 => {
                 state.pc = end + 1;
-                let obj = obj.get_parent(mem, operands[0])?;
+                let obj = handle(obj.get_parent(mem, operands[0]), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, obj)?;
             }
             0x04 => {
                 state.pc = end + 1;
-                let len = obj.prop_len(mem, operands[0])?;
+                let len = handle(obj.prop_len(mem, operands[0]), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, len)?;
             }
             0x05 => {
@@ -184,7 +192,10 @@ pub fn exec<I: Interface>(
                 if operands[0] & 0x8000 == 0 {
                     state.pc = end + operands[0] as usize - 2;
                 } else {
-                    state.pc = end - !operands[0] as usize - 3;
+                    state.pc = fatal(
+                        end.checked_sub(!operands[0] as usize + 3)
+                            .ok_or_else(|| err(Cause::PcOut, (0, 0))),
+                    )?;
                 }
             }
             0x0d => {
@@ -196,10 +207,13 @@ pub fn exec<I: Interface>(
             0x0e => {
                 state.pc = end + 1;
                 let val = if operands[0] == 0 {
-                    state.get_inplace()?
+                    state.get_inplace()
                 } else {
-                    state.get_var(mem, operands[0])?
+                    state.get_var(mem, operands[0])
                 };
+                let val = handle(val, |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, val)?;
             }
             0x0f if v < 5 => {
@@ -230,7 +244,7 @@ pub fn exec<I: Interface>(
                 instr.branch(mem, state, gt, false)?;
             }
             0x04 => {
-                let mut val = handle(state.get_var(mem, operands[0]), || {
+                let mut val = handle(state.get_var(mem, operands[0]), |_| {
                     instr.branch(mem, state, false, false)
                 })?;
                 val = alu::sub(val, 1);
@@ -238,7 +252,7 @@ pub fn exec<I: Interface>(
                 state.set_var(mem, operands[0], val)?;
             }
             0x05 => {
-                let mut val = handle(state.get_var(mem, operands[0]), || {
+                let mut val = handle(state.get_var(mem, operands[0]), |_| {
                     instr.branch(mem, state, false, false)
                 })?;
                 val = alu::add(val, 1);
@@ -246,7 +260,7 @@ pub fn exec<I: Interface>(
                 state.set_var(mem, operands[0], val)?;
             }
             0x06 => {
-                let parent = handle(obj.get_parent(mem, operands[0]), || {
+                let parent = handle(obj.get_parent(mem, operands[0]), |_| {
                     instr.branch(mem, state, 0 == operands[1], false)
                 })?;
                 instr.branch(mem, state, parent == operands[1], false)?;
@@ -264,7 +278,7 @@ pub fn exec<I: Interface>(
                 instr.store(mem, state, operands[0] & operands[1])?;
             }
             0x0a => {
-                let attr = handle(obj.test_attr(mem, operands[0], operands[1]), || {
+                let attr = handle(obj.test_attr(mem, operands[0], operands[1]), |_| {
                     instr.branch(mem, state, false, false)
                 })?;
                 instr.branch(mem, state, attr, false)?;
@@ -291,27 +305,38 @@ pub fn exec<I: Interface>(
             }
             0x0f => {
                 state.pc = end + 1;
-                let val = mem.loadw(operands[0] + 2 * operands[1])?;
+                let val = handle(
+                    mem.loadw(operands[0].wrapping_add(operands[1].wrapping_mul(2))),
+                    |err| instr.store(mem, state, config.error.fallback(err.cause)),
+                )?;
                 instr.store(mem, state, val)?;
             }
             0x10 => {
                 state.pc = end + 1;
-                let val = mem.loadb(operands[0] + operands[1])?;
+                let val = handle(mem.loadb(operands[0].wrapping_add(operands[1])), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, val)?;
             }
             0x11 => {
                 state.pc = end + 1;
-                let prop = obj.get_prop(mem, operands[0], operands[1])?;
+                let prop = handle(obj.get_prop(mem, operands[0], operands[1]), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, prop)?;
             }
             0x12 => {
                 state.pc = end + 1;
-                let addr = obj.prop_addr(mem, operands[0], operands[1])?;
+                let addr = handle(obj.prop_addr(mem, operands[0], operands[1]), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, addr)?;
             }
             0x13 => {
                 state.pc = end + 1;
-                let prop = obj.next_prop(mem, operands[0], operands[1])?;
+                let prop = handle(obj.next_prop(mem, operands[0], operands[1]), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, prop)?;
             }
             0x14 => {
@@ -331,12 +356,16 @@ pub fn exec<I: Interface>(
             }
             0x17 => {
                 state.pc = end + 1;
-                let result = alu::div(operands[0], operands[1])?;
+                let result = handle(alu::div(operands[0], operands[1]), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, result)?;
             }
             0x18 => {
                 state.pc = end + 1;
-                let result = alu::rem(operands[0], operands[1])?;
+                let result = handle(alu::rem(operands[0], operands[1]), |err| {
+                    instr.store(mem, state, config.error.fallback(err.cause))
+                })?;
                 instr.store(mem, state, result)?;
             }
             0x19 if v >= 4 => {
@@ -370,13 +399,13 @@ pub fn exec<I: Interface>(
             0x01 => {
                 state.pc = end;
                 check(operands, 3)?;
-                let addr = operands[0] + 2 * operands[1];
+                let addr = operands[0].wrapping_add(operands[1].wrapping_mul(2));
                 header.checked_storew(mem, addr, operands[2])?;
             }
             0x02 => {
                 state.pc = end;
                 check(operands, 3)?;
-                let addr = operands[0] + operands[1];
+                let addr = operands[0].wrapping_add(operands[1]);
                 header.checked_storeb(mem, addr, operands[2])?;
             }
             0x03 => {
@@ -420,7 +449,11 @@ pub fn exec<I: Interface>(
                 out.write(mem, text, interface, &str, 1)?;
 
                 if v >= 5 {
-                    let char = text.to_zscii_char(mem, char)?;
+                    // an interrupted read has no terminator
+                    let char = match char {
+                        '\0' => 0,
+                        char => text.to_zscii_char(mem, char)?,
+                    };
                     instr.store(mem, state, char)?;
                 }
             }
@@ -490,7 +523,7 @@ pub fn exec<I: Interface>(
                 state.pc = end;
                 let (row, col) = screen.get_cursor(interface);
                 header.checked_storew(mem, *get(operands, 0)?, row)?;
-                header.checked_storew(mem, operands[0] + 2, col)?;
+                header.checked_storew(mem, operands[0].wrapping_add(2), col)?;
             }
             0x11 if v >= 4 => {
                 state.pc = end;
@@ -510,7 +543,13 @@ pub fn exec<I: Interface>(
             }
             0x15 if v >= 5 => {
                 state.pc = end;
-                sound::bleep(interface, *get(operands, 0)?);
+                sound::effect(
+                    interface,
+                    config,
+                    *get(operands, 0)?,
+                    *operands.get(1).unwrap_or(&2),
+                    *operands.get(2).unwrap_or(&0xff),
+                );
             }
             0x16 if v >= 4 => {
                 state.pc = end + 1;
@@ -533,7 +572,13 @@ pub fn exec<I: Interface>(
                     err_said,
                 );
                 out.write(mem, text, interface, &char.to_string(), 2)?;
-                let char = text.to_zscii_char(mem, char)?;
+                // an interrupted read gives 0
+                let char = match char {
+                    '\0' => 0,
+                    char => handle(text.to_zscii_char(mem, char), |err| {
+                        instr.store(mem, state, config.error.fallback(err.cause))
+                    })?,
+                };
                 instr.store(mem, state, char)?;
             }
             0x17 if v >= 4 => {
@@ -544,16 +589,18 @@ pub fn exec<I: Interface>(
                 let table = operands[1];
                 if form & 0x80 != 0 {
                     for i in 0..operands[2] {
-                        if mem.loadw(table + i * size)? == operands[0] {
-                            instr.store(mem, state, table + i * size)?;
+                        let addr = table.wrapping_add(i.wrapping_mul(size));
+                        if mem.loadw(addr)? == operands[0] {
+                            instr.store(mem, state, addr)?;
                             instr.branch(mem, state, true, true)?;
                             break;
                         }
                     }
                 } else {
                     for i in 0..operands[2] {
-                        if mem.loadb(table + i * size)? == operands[0] {
-                            instr.store(mem, state, table + i * size)?;
+                        let addr = table.wrapping_add(i.wrapping_mul(size));
+                        if mem.loadb(addr)? == operands[0] {
+                            instr.store(mem, state, addr)?;
                             instr.branch(mem, state, true, true)?;
                             break;
                         }
@@ -585,9 +632,10 @@ pub fn exec<I: Interface>(
             0x1c if v >= 5 => {
                 state.pc = end;
                 check(operands, 4)?;
-                let result = text.encode(&mem, operands[0] + operands[2], operands[1])?;
+                let result =
+                    text.encode(mem, operands[0].wrapping_add(operands[2]), operands[1])?;
                 for (i, &res) in result.iter().enumerate() {
-                    mem.storew(operands[3] + 2 * i as u16, res)?;
+                    mem.storew(operands[3].wrapping_add(2 * i as u16), res)?;
                 }
             }
             0x1d if v >= 5 => {
@@ -598,24 +646,24 @@ pub fn exec<I: Interface>(
                 let size = operands[2];
                 if second == 0 {
                     for i in 0..size {
-                        mem.storeb(first + i, 0)?;
+                        mem.storeb(first.wrapping_add(i), 0)?;
                     }
                 } else if size & 0x8000 == 0 {
                     if first > second {
                         for i in 0..size {
-                            let val = mem.loadb(first + i)?;
-                            mem.storeb(second + i, val)?;
+                            let val = mem.loadb(first.wrapping_add(i))?;
+                            mem.storeb(second.wrapping_add(i), val)?;
                         }
                     } else {
                         for i in (0..size).rev() {
-                            let val = mem.loadb(first + i)?;
-                            mem.storeb(second + i, val)?;
+                            let val = mem.loadb(first.wrapping_add(i))?;
+                            mem.storeb(second.wrapping_add(i), val)?;
                         }
                     }
                 } else {
                     for i in 0..=!size {
-                        let val = mem.loadb(first + i)?;
-                        mem.storeb(second + i, val)?;
+                        let val = mem.loadb(first.wrapping_add(i))?;
+                        mem.storeb(second.wrapping_add(i), val)?;
                     }
                 }
             }
@@ -635,11 +683,11 @@ pub fn exec<I: Interface>(
                         if let Some(char) = text.decode_char(mem, char)? {
                             out.write(mem, text, interface, &format!("{}", char), 0)?;
                         }
-                        addr += 1;
+                        addr = addr.wrapping_add(1);
                     }
                     // let y = screen.get_cursor(interface).1;
                     // screen.set_cursor(interface, x, y + 1);
-                    addr += skip;
+                    addr = addr.wrapping_add(skip);
                 }
             }
             0x1f if v >= 5 => {
@@ -652,22 +700,38 @@ pub fn exec<I: Interface>(
             0x00 => {
                 let result = if operands.is_empty() {
                     state.pc = end;
-                    let save = state.save(mem)?;
+                    let save = state.save(mem, rand)?;
                     interface.save(&quetzal::save(mem, restart, &save, header.checksum))
                 } else {
-                    false
+                    let table = *get(operands, 0)?;
+                    let data = (0..*get(operands, 1)?)
+                        .map(|i| mem.loadb(table.wrapping_add(i)).map(|byte| byte as u8))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    interface.save_aux(&aux_name(mem, operands.get(2))?, &data)
                 };
                 state.pc = end + 1;
                 instr.store(mem, state, if result { 1 } else { 0 })?;
             }
+            0x01 if !operands.is_empty() => {
+                state.pc = end + 1;
+                let table = *get(operands, 0)?;
+                let data = interface
+                    .restore_aux(&aux_name(mem, operands.get(2))?)
+                    .unwrap_or_default();
+                let len = data.len().min(usize::from(*get(operands, 1)?));
+                for (i, &byte) in data[..len].iter().enumerate() {
+                    mem.storeb(table.wrapping_add(i as u16), byte.into())?;
+                }
+                instr.store(mem, state, len as u16)?;
+            }
             0x01 => {
                 let save = quetzal::restore(restart, &interface.restore(), header.checksum);
                 match save {
                     Ok(save) => {
-                        state.restore(mem, &save);
+                        state.restore(mem, rand, &save);
+                        let var = mem[state.pc];
                         state.pc += 1;
                         header::init(mem, config)?;
-                        let var = mem[state.pc];
                         state.set_var(mem, var.into(), 2)?;
                     }
                     Err(cause) => {
@@ -690,15 +754,24 @@ pub fn exec<I: Interface>(
             0x04 => {
                 state.pc = end + 1;
                 let font = screen.font(interface, *get(operands, 0)?);
+                out.graphics(screen.graphics());
                 instr.store(mem, state, font)?;
             }
             0x09 => {
                 state.pc = end + 1;
-                undo[0] = state.save(mem)?;
-                instr.store(mem, state, 1)?;
+                if config.undo {
+                    undo[0] = state.save(mem, rand)?;
+                    instr.store(mem, state, 1)?;
+                } else {
+                    instr.store(mem, state, 0xffff)?;
+                }
+            }
+            0x0a if !config.undo => {
+                state.pc = end + 1;
+                instr.store(mem, state, 0)?;
             }
             0x0a => {
-                state.restore(mem, &undo[0]);
+                state.restore(mem, rand, &undo[0]);
                 let var = *fatal(
                     mem.get(state.pc - 1)
                         .ok_or_else(|| err(Cause::PcOut, (0, 0))),
@@ -717,11 +790,9 @@ pub fn exec<I: Interface>(
                 use std::char;
                 state.pc = end + 1;
                 let check = if let Some(char) = char::from_u32(u32::from(*get(operands, 0)?)) {
-                    if char == '?' || text.to_zscii_char(mem, char)? != 0x3f {
-                        3
-                    } else {
-                        1
-                    }
+                    let (print, read) = interface.check_unicode(char);
+                    let read = read && (char == '?' || text.to_zscii_char(mem, char)? != 0x3f);
+                    u16::from(print) | u16::from(read) << 1
                 } else {
                     0
                 };
@@ -741,6 +812,27 @@ pub fn exec<I: Interface>(
     Ok(())
 }
 
+/// The name of an auxiliary file, lowercased and with the `.aux` extension if it has none
+///
+/// The name is a length-prefixed string at `addr`, and empty if there's no such operand.
+fn aux_name(mem: &mem::Mem, addr: Option<&u16>) -> Result<String, Error> {
+    let Some(&addr) = addr else {
+        return Ok(String::new());
+    };
+    let mut name = (1..=mem.loadb(addr)?)
+        .map(|i| {
+            mem.loadb(addr.wrapping_add(i))
+                .map(|char| char as u8 as char)
+        })
+        .collect::<Result<String, _>>()?
+        .to_ascii_lowercase();
+    name.retain(|char| char.is_ascii_alphanumeric() || char == '.');
+    if !name.is_empty() && !name.contains('.') {
+        name.push_str(".aux");
+    }
+    Ok(name)
+}
+
 fn get(operands: &[u16], i: usize) -> Result<&u16, Error> {
     operands
         .get(i)
@@ -755,12 +847,199 @@ fn check(operands: &[u16], min: usize) -> Result<(), Error> {
     }
 }
 
+/// The instruction stores a result
+pub const STORE: u8 = 0x01;
+/// The instruction branches
+pub const BRANCH: u8 = 0x02;
+/// The instruction is followed by a string
+pub const TEXT: u8 = 0x04;
+/// The first operand of the instruction is a routine to call
+pub const CALL: u8 = 0x08;
+/// The execution doesn't continue after the instruction
+pub const END: u8 = 0x10;
+
+/// The name of an opcode, as in the standard
+pub fn name(v: u8, count: u8, opcode: u8) -> &'static str {
+    info(v, count, opcode).map_or("unknown", |(name, _)| name)
+}
+
+/// The name of an opcode and what follows it (`None` if it doesn't exist in this version)
+pub fn info(v: u8, count: u8, opcode: u8) -> Option<(&'static str, u8)> {
+    Some(match (count, opcode) {
+        (0, 0x00) => ("rtrue", END),
+        (0, 0x01) => ("rfalse", END),
+        (0, 0x02) => ("print", TEXT),
+        (0, 0x03) => ("print_ret", TEXT | END),
+        (0, 0x04) => ("nop", 0),
+        (0, 0x05) if v < 4 => ("save", BRANCH),
+        (0, 0x05) if v == 4 => ("save", STORE),
+        (0, 0x06) if v < 4 => ("restore", BRANCH),
+        (0, 0x06) if v == 4 => ("restore", STORE),
+        (0, 0x07) => ("restart", END),
+        (0, 0x08) => ("ret_popped", END),
+        (0, 0x09) if v < 5 => ("pop", 0),
+        (0, 0x09) => ("catch", STORE),
+        (0, 0x0a) => ("quit", END),
+        (0, 0x0b) => ("new_line", 0),
+        (0, 0x0c) if v == 3 => ("show_status", 0),
+        (0, 0x0d) => ("verify", BRANCH),
+        (0, 0x0f) => ("piracy", BRANCH),
+        (1, 0x00) => ("jz", BRANCH),
+        (1, 0x01) => ("get_sibling", STORE | BRANCH),
+        (1, 0x02) => ("get_child", STORE | BRANCH),
+        (1, 0x03) => ("get_parent", STORE),
+        (1, 0x04) => ("get_prop_len", STORE),
+        (1, 0x05) => ("inc", 0),
+        (1, 0x06) => ("dec", 0),
+        (1, 0x07) => ("print_addr", 0),
+        (1, 0x08) if v >= 4 => ("call_1s", CALL | STORE),
+        (1, 0x09) => ("remove_obj", 0),
+        (1, 0x0a) => ("print_obj", 0),
+        (1, 0x0b) => ("ret", END),
+        (1, 0x0c) => ("jump", END),
+        (1, 0x0d) => ("print_paddr", 0),
+        (1, 0x0e) => ("load", STORE),
+        (1, 0x0f) if v < 5 => ("not", STORE),
+        (1, 0x0f) => ("call_1n", CALL),
+        (2, 0x01) => ("je", BRANCH),
+        (2, 0x02) => ("jl", BRANCH),
+        (2, 0x03) => ("jg", BRANCH),
+        (2, 0x04) => ("dec_chk", BRANCH),
+        (2, 0x05) => ("inc_chk", BRANCH),
+        (2, 0x06) => ("jin", BRANCH),
+        (2, 0x07) => ("test", BRANCH),
+        (2, 0x08) => ("or", STORE),
+        (2, 0x09) => ("and", STORE),
+        (2, 0x0a) => ("test_attr", BRANCH),
+        (2, 0x0b) => ("set_attr", 0),
+        (2, 0x0c) => ("clear_attr", 0),
+        (2, 0x0d) => ("store", 0),
+        (2, 0x0e) => ("insert_obj", 0),
+        (2, 0x0f) => ("loadw", STORE),
+        (2, 0x10) => ("loadb", STORE),
+        (2, 0x11) => ("get_prop", STORE),
+        (2, 0x12) => ("get_prop_addr", STORE),
+        (2, 0x13) => ("get_next_prop", STORE),
+        (2, 0x14) => ("add", STORE),
+        (2, 0x15) => ("sub", STORE),
+        (2, 0x16) => ("mul", STORE),
+        (2, 0x17) => ("div", STORE),
+        (2, 0x18) => ("mod", STORE),
+        (2, 0x19) if v >= 4 => ("call_2s", CALL | STORE),
+        (2, 0x1a) if v >= 5 => ("call_2n", CALL),
+        (2, 0x1b) if v >= 5 => ("set_colour", 0),
+        (2, 0x1c) if v >= 5 => ("throw", END),
+        (3, 0x00) if v < 4 => ("call", CALL | STORE),
+        (3, 0x00) => ("call_vs", CALL | STORE),
+        (3, 0x01) => ("storew", 0),
+        (3, 0x02) => ("storeb", 0),
+        (3, 0x03) => ("put_prop", 0),
+        (3, 0x04) if v < 5 => ("sread", 0),
+        (3, 0x04) => ("aread", STORE),
+        (3, 0x05) => ("print_char", 0),
+        (3, 0x06) => ("print_num", 0),
+        (3, 0x07) => ("random", STORE),
+        (3, 0x08) => ("push", 0),
+        (3, 0x09) => ("pull", 0),
+        (3, 0x0a) if v >= 3 => ("split_window", 0),
+        (3, 0x0b) if v >= 3 => ("set_window", 0),
+        (3, 0x0c) if v >= 4 => ("call_vs2", CALL | STORE),
+        (3, 0x0d) if v >= 4 => ("erase_window", 0),
+        (3, 0x0e) if v >= 4 => ("erase_line", 0),
+        (3, 0x0f) if v >= 4 => ("set_cursor", 0),
+        (3, 0x10) if v >= 4 => ("get_cursor", 0),
+        (3, 0x11) if v >= 4 => ("set_text_style", 0),
+        (3, 0x12) if v >= 4 => ("buffer_mode", 0),
+        (3, 0x13) if v >= 3 => ("output_stream", 0),
+        (3, 0x14) if v >= 3 => ("input_stream", 0),
+        (3, 0x15) if v >= 3 => ("sound_effect", 0),
+        (3, 0x16) if v >= 4 => ("read_char", STORE),
+        (3, 0x17) if v >= 4 => ("scan_table", STORE | BRANCH),
+        (3, 0x18) if v >= 5 => ("not", STORE),
+        (3, 0x19) if v >= 5 => ("call_vn", CALL),
+        (3, 0x1a) if v >= 5 => ("call_vn2", CALL),
+        (3, 0x1b) if v >= 5 => ("tokenise", 0),
+        (3, 0x1c) if v >= 5 => ("encode_text", 0),
+        (3, 0x1d) if v >= 5 => ("copy_table", 0),
+        (3, 0x1e) if v >= 5 => ("print_table", 0),
+        (3, 0x1f) if v >= 5 => ("check_arg_count", BRANCH),
+        (4, 0x00) => ("save", STORE),
+        (4, 0x01) => ("restore", STORE),
+        (4, 0x02) => ("log_shift", STORE),
+        (4, 0x03) => ("art_shift", STORE),
+        (4, 0x04) => ("set_font", STORE),
+        (4, 0x09) => ("save_undo", STORE),
+        (4, 0x0a) => ("restore_undo", STORE),
+        (4, 0x0b) => ("print_unicode", 0),
+        (4, 0x0c) => ("check_unicode", STORE),
+        (4, 0x0d) => ("set_true_colour", 0),
+        _ => return None,
+    })
+}
+
 fn handle<T, F>(result: Result<T, Error>, fallback: F) -> Result<T, Error>
 where
-    F: FnOnce() -> Result<(), Error>,
+    F: FnOnce(&Error) -> Result<(), Error>,
 {
-    if result.is_err() {
-        fallback().or_else(|err| if err.fatal { Err(err) } else { Ok(()) })?;
+    if let Err(ref err) = result {
+        fallback(err).or_else(|err| if err.fatal { Err(err) } else { Ok(()) })?;
     }
     result
 }
+
+#[test]
+fn test_name() {
+    assert_eq!(name(3, 0, 0x09), "pop");
+    assert_eq!(name(5, 0, 0x09), "catch");
+    assert_eq!(name(5, 2, 0x11), "get_prop");
+    assert_eq!(name(5, 3, 0x04), "aread");
+    assert_eq!(name(5, 4, 0x0c), "check_unicode");
+    assert_eq!(name(5, 2, 0x00), "unknown");
+    assert_eq!(name(3, 3, 0x0c), "unknown");
+    assert_eq!(info(3, 3, 0x15), Some(("sound_effect", 0)));
+}
+
+#[test]
+fn test_read_char_interrupted() {
+    // read_char 1 -> sp, print_num sp, quit
+    let code = [0xf6, 0x7f, 0x01, 0x00, 0xe6, 0xbf, 0x00, 0xba];
+    let (output, err) = machine::play(machine::story(&code), "\0");
+    assert_eq!((output.as_str(), err.cause), ("0", Cause::Quit));
+}
+
+#[test]
+fn test_restore_ext() {
+    // save -> g0, print_num g0, new_line
+    let mut code = vec![0xbe, 0x00, 0xff, 0x10, 0xe6, 0xbf, 0x10, 0xbb];
+    // je g0 2 ?quit, restore -> g0, quit
+    code.extend(&[0x41, 0x10, 0x02, 0xc6, 0xbe, 0x01, 0xff, 0x10, 0xba]);
+    // the restore stores 2 in the variable of the save instruction
+    let (output, err) = machine::play(machine::story(&code), "");
+    assert_eq!((output.as_str(), err.cause), ("1\n2\n", Cause::Quit));
+}
+
+#[test]
+fn test_restart() {
+    const TABLE: u16 = 0x300;
+    // print_num 1, new_line, output_stream 2, output_stream 3 TABLE, set_font 4 -> sp, restart
+    let mut first = vec![0xe6, 0x7f, 0x01, 0xbb, 0xf3, 0x7f, 0x02, 0xf3, 0x4f, 0x03];
+    first.extend(&TABLE.to_be_bytes());
+    first.extend(&[0xbe, 0x04, 0x7f, 0x04, 0x00, 0xb7]);
+    // print_num 2, new_line, set_font 0 -> sp, print_num sp, new_line, quit
+    let second = [
+        0xe6, 0x7f, 0x02, 0xbb, 0xbe, 0x04, 0x7f, 0x00, 0x00, 0xe6, 0xbf, 0x00, 0xbb, 0xba,
+    ];
+    // loadb $11 0 -> sp, test sp 1 ?second
+    let mut code = vec![0x10, 0x11, 0x00, 0x00, 0x47, 0x00, 0x01];
+    code.push(0xc0 | (first.len() as u8 + 2));
+    code.extend(&first);
+    code.extend(&second);
+
+    // the transcript bit survives, the streams and the font are reset
+    let (test, err) = machine::play_with(machine::story(&code), "", interface::DEFAULT);
+    assert_eq!(
+        (test.output.as_str(), err.cause),
+        ("1\n2\n1\n", Cause::Quit)
+    );
+    assert_eq!(test.transcript, "2\n1\n");
+}
@@ -0,0 +1,194 @@
+//! Compatibility patches
+//!
+//! Some releases of games have bugs that interpreters work around by replacing a few bytes of the storyfile when loading it.
+//! A patch applies to the story with its release number, serial number and checksum, and only if the bytes it replaces are the expected ones.
+//! The built-in patches are always applied, more can be read with [`parse_patches`] and given in [`Config::patches`].
+
+use crate::{err::*, header::Header, interface::Config, mem::Mem};
+
+/// A replacement of bytes in a storyfile
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patch {
+    /// The release number of the story
+    pub release: u16,
+    /// The serial number of the story
+    pub serial: [u8; 6],
+    /// The checksum of the story
+    pub checksum: u16,
+    /// The address of the bytes
+    pub addr: usize,
+    /// The bytes expected at this address
+    pub original: Vec<u8>,
+    /// The bytes replacing them
+    pub replacement: Vec<u8>,
+}
+
+const BUILTIN: &str = include_str!("patches.txt");
+
+/// Reads a file of patches
+///
+/// Each line holds a patch: the release number, the serial number, the checksum and the address in hexadecimal,
+/// then the original bytes and the replacement bytes in hexadecimal, separated by spaces.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_patches(text: &str) -> Result<Vec<Patch>, Error> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(n, line)| parse(line).ok_or_else(|| err(Cause::BadPatch, (n as u16 + 1, 0))))
+        .collect()
+}
+
+fn parse(line: &str) -> Option<Patch> {
+    let mut fields = line.split_whitespace();
+    let release = fields.next()?.parse().ok()?;
+    let serial = fields.next()?.as_bytes().try_into().ok()?;
+    let checksum = u16::from_str_radix(fields.next()?, 16).ok()?;
+    let addr = usize::from_str_radix(fields.next()?, 16).ok()?;
+    let original = bytes(fields.next()?)?;
+    let replacement = bytes(fields.next()?)?;
+    if fields.next().is_some() || original.len() != replacement.len() {
+        return None;
+    }
+    Some(Patch {
+        release,
+        serial,
+        checksum,
+        addr,
+        original,
+        replacement,
+    })
+}
+
+fn bytes(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Applies the built-in patches and the ones of the `config` matching the story. Returns the number of patches applied.
+pub fn apply(mem: &mut Mem, header: &Header, config: &Config) -> usize {
+    let builtin = parse_patches(BUILTIN).unwrap();
+    let release = mem.loadw(0x02).unwrap();
+    let mut applied = 0;
+    for patch in builtin.iter().chain(config.patches) {
+        if patch.release == release
+            && patch.serial[..] == mem.story()[0x12..0x18]
+            && patch.checksum == header.checksum
+            && mem
+                .story()
+                .get(patch.addr..patch.addr + patch.original.len())
+                == Some(&patch.original)
+        {
+            mem.patch(patch.addr, &patch.replacement);
+            applied += 1;
+        }
+    }
+    applied
+}
+
+#[cfg(test)]
+use crate::{header, interface, mem};
+
+#[test]
+fn test_parse() {
+    assert!(parse_patches(BUILTIN).is_ok());
+    let patches = parse_patches("# comment\n\n12 870430 1a2b 3f 00ff b4b4\n").unwrap();
+    assert_eq!(
+        patches,
+        vec![Patch {
+            release: 12,
+            serial: *b"870430",
+            checksum: 0x1a2b,
+            addr: 0x3f,
+            original: vec![0x00, 0xff],
+            replacement: vec![0xb4, 0xb4],
+        }]
+    );
+    for (text, line) in [
+        ("12 87043 1a2b 3f 00 b4", 1),
+        ("\n12 870430 1a2b 3f 00ff b4", 2),
+        ("# comment\n12 870430 1a2b 3f 0 b", 2),
+        ("12 870430 1a2b 3f 00 b4 00", 1),
+        ("12 870430 1a2b", 1),
+    ] {
+        let err = parse_patches(text).unwrap_err();
+        assert_eq!((err.cause, err.data), (Cause::BadPatch, (line, 0)));
+    }
+}
+
+#[test]
+fn test_apply() {
+    let mut data = mem::default();
+    data[0x03] = 12;
+    data[0x12..0x18].copy_from_slice(b"870430");
+    data[0x1b] = 0x22;
+    data.extend(vec![0x01, 0x02, 0x03]);
+    let mut mem = mem::new(data).unwrap();
+    let header = header::init_test(&mut mem);
+    let patch = Patch {
+        release: 12,
+        serial: *b"870430",
+        checksum: 6,
+        addr: 0x41,
+        original: vec![0x02, 0x03],
+        replacement: vec![0x04, 0x05],
+    };
+    let patches = vec![
+        patch.clone(),
+        Patch {
+            release: 13,
+            ..patch.clone()
+        },
+        Patch {
+            checksum: 7,
+            ..patch.clone()
+        },
+        Patch {
+            original: vec![0x02, 0x02],
+            ..patch.clone()
+        },
+        Patch {
+            addr: 0x42,
+            ..patch
+        },
+    ];
+    let config = Config {
+        patches: &patches,
+        ..interface::DEFAULT
+    };
+    assert_eq!(apply(&mut mem, &header, &config), 1);
+    assert_eq!([mem[0x40], mem[0x41], mem[0x42]], [0x01, 0x04, 0x05]);
+
+    // each built-in patch applies to a story with its numbers and its original bytes
+    for patch in parse_patches(BUILTIN).unwrap() {
+        let mut data = mem::default();
+        data[0x00] = 5;
+        data[0x02..0x04].copy_from_slice(&patch.release.to_be_bytes());
+        data[0x12..0x18].copy_from_slice(&patch.serial);
+        data.resize(patch.addr, 0);
+        data.extend(&patch.original);
+        // bytes after the patch give the checksum
+        let mut checksum = data[0x40..]
+            .iter()
+            .fold(0u16, |sum, &b| sum.wrapping_add(b.into()));
+        while checksum != patch.checksum {
+            let byte = patch.checksum.wrapping_sub(checksum).min(0xff) as u8;
+            data.push(byte);
+            checksum = checksum.wrapping_add(byte.into());
+        }
+        data.resize(data.len().next_multiple_of(4), 0);
+        let len = (data.len() / 4) as u16;
+        data[0x1a..0x1c].copy_from_slice(&len.to_be_bytes());
+        let mut mem = mem::new(data).unwrap();
+        let header = header::init_test(&mut mem);
+        assert_eq!(apply(&mut mem, &header, &interface::DEFAULT), 1);
+        let patched: Vec<_> = (0..patch.replacement.len())
+            .map(|i| mem[patch.addr + i])
+            .collect();
+        assert_eq!(patched, patch.replacement);
+    }
+}
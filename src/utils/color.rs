@@ -65,7 +65,7 @@ fn gray(color: i16) -> i16 {
             16..=20 => 3,
             21..=25 => 4,
             26..=28 => 5,
-            29 | 30 | 31 => color - 23,
+            29..=31 => color - 23,
             _ => unreachable!(),
         }
 }
@@ -10,9 +10,10 @@ impl IO {
         'main: loop {
             print!("\nEnter file name: ");
             let path = PathBuf::from(
-                self.read(vec!['\n'], String::new(), ::std::usize::MAX, 0, |_| false)
+                self.read(vec!['\n'], String::new(), usize::MAX, 0, |_, _| false)
                     .0,
             );
+            println!();
             if path.exists() {
                 print!("This file already exists. ");
                 loop {
@@ -41,8 +42,9 @@ impl IO {
         loop {
             print!("\nEnter file name: ");
             let path = self
-                .read(vec!['\n'], String::new(), ::std::usize::MAX, 0, |_| false)
+                .read(vec!['\n'], String::new(), usize::MAX, 0, |_, _| false)
                 .0;
+            println!();
             match File::open(path) {
                 Ok(file) => return BufReader::new(file),
                 Err(err) => println!("Error opening the file: {}", err),
@@ -2,12 +2,12 @@ use rzif::{Error, *};
 use std::{
     fs::File,
     io::*,
+    sync::atomic::{AtomicBool, Ordering},
     time::{Duration, Instant},
 };
 
 mod buffer;
 mod color;
-mod err;
 mod file;
 mod getch;
 mod read;
@@ -21,7 +21,6 @@ pub struct IO {
     buffering: bool,
     getch: getch::Getch,
     hist: Vec<String>,
-    printed: bool,
     transcript: Option<BufWriter<File>>,
     cmd_out: Option<BufWriter<File>>,
     cmd_in: Option<BufReader<File>>,
@@ -30,7 +29,6 @@ pub struct IO {
 impl Interface for IO {
     fn write_screen(&mut self, str: &str, _: bool) {
         self.buffer(str, true);
-        self.printed = true;
     }
 
     fn write_transcript(&mut self, str: &str) {
@@ -60,10 +58,7 @@ impl Interface for IO {
     }
 
     fn window_font(&mut self, font: u16) -> bool {
-        match font {
-            1 | 4 => true,
-            _ => false,
-        }
+        matches!(font, 1 | 4)
     }
 
     fn window_color(&mut self, foreground: u16, background: u16) {
@@ -131,7 +126,7 @@ impl Interface for IO {
 
     fn window_cursor_set(&mut self, x: u16, y: u16) {
         if self.current == 1 {
-        	print!("\x1b[{};{}H", x, y);
+            print!("\x1b[{};{}H", x, y);
         }
     }
 
@@ -201,7 +196,7 @@ impl Interface for IO {
         self.buffer("\x1b[K", false);
     }
 
-    fn read<T: FnMut(&mut Self) -> bool>(
+    fn read<T: FnMut(&mut Self, &str) -> bool>(
         &mut self,
         terminate: Vec<char>,
         left: String,
@@ -260,20 +255,60 @@ impl Interface for IO {
         if let SaveError::GamesDiffer = cause {
             println!("This file wasn't saved from this game");
         } else {
-            println!("Corrupted save file: {:?}", cause);
+            println!("Corrupted save file: {}", cause);
+        }
+    }
+
+    fn save_aux(&mut self, name: &str, data: &[u8]) -> bool {
+        if name.is_empty() {
+            return self.save(data);
+        }
+        File::create(name)
+            .and_then(|mut file| file.write_all(data))
+            .is_ok()
+    }
+
+    fn restore_aux(&mut self, name: &str) -> Option<Vec<u8>> {
+        if name.is_empty() {
+            return Some(self.restore());
+        }
+        std::fs::read(name).ok()
+    }
+
+    fn resized(&mut self) -> Option<(u16, u16)> {
+        if !RESIZED.swap(false, Ordering::Relaxed) {
+            return None;
         }
+        self.size = crate::get_size();
+        print!(
+            "\x1b7\x1b[{};{}r\x1b8",
+            self.split + if self.v == 3 { 2 } else { 1 },
+            self.size.1
+        );
+        Some(self.size)
     }
 
     fn error(&mut self, error: Error) {
         self.flush();
-        if error.cause == Cause::Quit {
-            return;
-        }
-        err::display(error);
+        eprintln!("{}", error);
     }
 }
 
+/// Set when the terminal is resized
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+const SIGWINCH: i32 = 28;
+
+extern "C" fn on_resize(_: i32) {
+    RESIZED.store(true, Ordering::Relaxed);
+}
+
+extern "C" {
+    fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+}
+
 pub fn init(v: u8, w: u16, h: u16) -> IO {
+    unsafe { signal(SIGWINCH, on_resize) };
     print!("\x1b[2J");
     if v <= 3 {
         print!("\x1b[2;{}r", h);
@@ -292,7 +327,6 @@ pub fn init(v: u8, w: u16, h: u16) -> IO {
         buffering: true,
         getch: getch::init(),
         hist: Vec::new(),
-        printed: false,
         transcript: None,
         cmd_out: None,
         cmd_in: None,
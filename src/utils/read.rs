@@ -2,7 +2,7 @@ use crate::utils::IO;
 use std::time::{Duration, Instant};
 
 impl IO {
-    pub fn read<T: FnMut(&mut Self) -> bool>(
+    pub fn read<T: FnMut(&mut Self, &str) -> bool>(
         &mut self,
         terminate: Vec<char>,
         left: String,
@@ -19,18 +19,18 @@ impl IO {
         let mut last = Instant::now();
         loop {
             if timed && last.elapsed() >= time {
-                self.printed = false;
-                if callback(self) {
+                let offset = buf.len() - i;
+                if offset != 0 {
+                    print!("\x1b[{}C", offset);
+                }
+                if callback(self, &buf.iter().collect::<String>()) {
                     return (end(self, &buf), '\0');
                 }
-                last = Instant::now();
-                if self.printed {
-                    print!("{}", buf.iter().collect::<String>());
-                    let offset = buf.len() - i;
-                    if offset != 0 {
-                        print!("\x1b[{}D", offset);
-                    }
+                self.flush();
+                if offset != 0 {
+                    print!("\x1b[{}D", offset);
                 }
+                last = Instant::now();
             }
             if let Some(char) = self.getch.getch() {
                 if char == '\n' || terminate.contains(&char) {
@@ -137,7 +137,6 @@ fn init(str: &str, i: usize) -> Vec<char> {
 }
 
 fn end(io: &mut IO, buf: &[char]) -> String {
-    println!();
     let str = buf.iter().collect::<String>();
     io.hist.push(str.clone());
     str
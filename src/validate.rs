@@ -0,0 +1,36 @@
+use std::{env::Args, fs, process};
+
+pub fn main(name: &str, mut args: Args) {
+    let path = match args.next() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: {} validate path/to/storyfile", name);
+            process::exit(2);
+        }
+    };
+    let story = match fs::read(&path) {
+        Ok(story) => story,
+        Err(err) => {
+            eprintln!("Error opening the file {}: {}", path, err);
+            process::exit(2);
+        }
+    };
+
+    match rzif::validate(&story) {
+        Ok(errors) if errors.is_empty() => println!("No problem found"),
+        Ok(errors) => {
+            for error in errors.iter() {
+                println!("{}", error.details());
+                for frame in error.trace.iter().rev() {
+                    println!("  while {}", frame);
+                }
+            }
+            println!("{} problems found", errors.len());
+            process::exit(1);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    }
+}
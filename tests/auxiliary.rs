@@ -0,0 +1,57 @@
+mod common;
+
+// Layout of the story
+const TABLE: u16 = 0x300;
+const NAMES: [u16; 3] = [0x310, 0x320, 0x330];
+
+/// `opcode` with the operands `table`, `bytes` and `name`, storing to the stack
+fn aux(opcode: u8, bytes: u8, name: u16) -> Vec<u8> {
+    let mut code = vec![0xbe, opcode, 0x13];
+    code.extend(&TABLE.to_be_bytes());
+    code.push(bytes);
+    code.extend(&name.to_be_bytes());
+    code.push(0x00);
+    code
+}
+
+/// A story saving a table, changing it and restoring it
+fn story() -> Vec<u8> {
+    // print_num sp, new_line
+    let print = [0xe6, 0xbf, 0x00, 0xbb];
+    // save, storeb TABLE 0 'x', restore, print the table
+    let mut code = aux(0x00, 3, NAMES[0]);
+    code.extend(&print);
+    code.extend(&[0xe2, 0x17]);
+    code.extend(&TABLE.to_be_bytes());
+    code.extend(&[0x00, b'x']);
+    code.extend(aux(0x01, 3, NAMES[1]));
+    code.extend(&print);
+    for i in 0..3 {
+        // loadb TABLE i -> sp, print_char sp
+        code.extend(&[0xd0, 0x1f]);
+        code.extend(&TABLE.to_be_bytes());
+        code.extend(&[i, 0x00, 0xe5, 0xbf, 0x00]);
+    }
+    code.push(0xbb);
+    // restore fewer bytes than saved, then from a missing file, quit
+    code.extend(aux(0x01, 2, NAMES[1]));
+    code.extend(&print);
+    code.extend(aux(0x01, 3, NAMES[2]));
+    code.extend(&print);
+    code.push(0xba);
+
+    let mut data = common::story_with(&code);
+    data[TABLE as usize..TABLE as usize + 3].copy_from_slice(b"abc");
+    for (&addr, name) in NAMES.iter().zip(["Scores", "SCORES", "none"]) {
+        data[addr as usize] = name.len() as u8;
+        data[addr as usize + 1..][..name.len()].copy_from_slice(name.as_bytes());
+    }
+    data
+}
+
+#[test]
+fn table() {
+    let (str, _, err) = common::main(story(), Vec::new().into_iter(), Vec::new().into_iter());
+    common::check(&str, "1\n3\nabc\n2\n0\n", true);
+    common::errors(err, Vec::new());
+}
@@ -1,5 +1,8 @@
+#![allow(dead_code)]
+
 extern crate rzif;
 use self::rzif::*;
+use std::collections::HashMap;
 
 struct IO<'a, L: 'a + Iterator, C: 'a + Iterator> {
     output: &'a mut String,
@@ -7,6 +10,7 @@ struct IO<'a, L: 'a + Iterator, C: 'a + Iterator> {
     lines: &'a mut L,
     chars: &'a mut C,
     errors: &'a mut Vec<Error>,
+    aux: HashMap<String, Vec<u8>>,
 }
 
 impl<'a, L, C> Interface for IO<'a, L, C>
@@ -25,10 +29,7 @@ where
 
     fn status(&mut self, _: &str) {}
     fn window_font(&mut self, font: u16) -> bool {
-        match font {
-            1 | 4 => true,
-            _ => false,
-        }
+        matches!(font, 1 | 4)
     }
     fn window_color(&mut self, _: u16, _: u16) {}
     fn window_style(&mut self, _: u16) {}
@@ -42,7 +43,7 @@ where
     fn window_erase(&mut self, _: u16) {}
     fn window_line(&mut self) {}
 
-    fn read<T: FnMut(&mut Self) -> bool>(
+    fn read<T: FnMut(&mut Self, &str) -> bool>(
         &mut self,
         _: Vec<char>,
         _: String,
@@ -51,7 +52,7 @@ where
         _: T,
     ) -> (String, char) {
         let line = self.lines.next().unwrap();
-        print!("{}{}", line.0, line.1);
+        print!("{}", line.0);
         line
     }
     fn read_char<T: FnMut(&mut Self) -> bool>(&mut self, _: u16, _: T) -> char {
@@ -69,13 +70,34 @@ where
         Vec::new()
     }
     fn restore_failed(&mut self, _: SaveError) {}
+    fn save_aux(&mut self, name: &str, data: &[u8]) -> bool {
+        self.aux.insert(name.to_string(), data.to_vec());
+        true
+    }
+    fn restore_aux(&mut self, name: &str) -> Option<Vec<u8>> {
+        self.aux.get(name).cloned()
+    }
     fn error(&mut self, error: Error) {
         println!("{:?}", error);
         self.errors.push(error);
     }
 }
 
-pub fn main<L, C>(file: Vec<u8>, mut lines: L, mut chars: C) -> (String, String, Vec<Error>)
+pub fn main<L, C>(file: Vec<u8>, lines: L, chars: C) -> (String, String, Vec<Error>)
+where
+    L: Iterator<Item = (String, char)>,
+    C: Iterator<Item = char>,
+{
+    main_with(file, lines, chars, ErrorPolicy::new(ErrorLevel::Always))
+}
+
+/// Like [`main`], handling the errors with the `error` policy
+pub fn main_with<L, C>(
+    file: Vec<u8>,
+    mut lines: L,
+    mut chars: C,
+    error: ErrorPolicy,
+) -> (String, String, Vec<Error>)
 where
     L: Iterator<Item = (String, char)>,
     C: Iterator<Item = char>,
@@ -96,19 +118,32 @@ where
         default_color: (2, 9),
         true_color: (0x0000, 0x7fff),
         picture: false,
-        error: ErrorLevel::Always,
+        graphics: false,
+        undo: true,
+        sound: false,
+        transparency: false,
+        patches: &[],
+        error,
+        seed: None,
+        generator: Generator::Lcg,
     };
-    rzif::main(
+    let outcome = rzif::main(
         file,
         config,
-        IO {
+        &mut IO {
             output: &mut output,
             trans: &mut trans,
             lines: &mut lines,
             chars: &mut chars,
             errors: &mut errors,
+            aux: HashMap::new(),
         },
     );
+    match outcome {
+        Outcome::Quit => {}
+        Outcome::Stopped => panic!("\nThe game was stopped"),
+        Outcome::Fatal(error) => errors.push(error),
+    }
     let lines = lines.collect::<Vec<_>>();
     if lines != Vec::new() {
         panic!("\nUnused input lines: {:?}", lines);
@@ -143,8 +178,7 @@ pub fn check(str: &str, exept: &str, end: bool) {
     }
 }
 
-pub fn errors(err: Vec<Error>, mut exept: Vec<(Cause, (u16, u16))>) {
-    exept.push((Cause::Quit, (0, 0)));
+pub fn errors(err: Vec<Error>, exept: Vec<(Cause, (u16, u16))>) {
     let mut err = err.into_iter();
     for (cause, data) in exept.into_iter() {
         let err = err.next().unwrap();
@@ -153,3 +187,72 @@ pub fn errors(err: Vec<Error>, mut exept: Vec<(Cause, (u16, u16))>) {
     }
     assert!(err.next().is_none());
 }
+
+/// Where a synthetic story puts its tables and its code
+#[derive(Clone, Copy)]
+pub struct Layout {
+    pub version: u8,
+    pub globals: usize,
+    pub dict: usize,
+    pub obj: usize,
+    /// The start of static memory
+    pub stat: usize,
+    /// The start of high memory, and the initial PC
+    pub code: usize,
+}
+
+/// A version 5 story with its code at 0x400
+pub const LAYOUT: Layout = Layout {
+    version: 5,
+    globals: 0x40,
+    dict: 0x220,
+    obj: 0x224,
+    stat: 0x400,
+    code: 0x400,
+};
+
+pub fn set(data: &mut [u8], addr: usize, word: usize) {
+    data[addr] = (word >> 8) as u8;
+    data[addr + 1] = word as u8;
+}
+
+/// Pack z-chars into a string, padded with 5s
+pub fn pack(mut zchars: Vec<u8>) -> Vec<u8> {
+    while !zchars.len().is_multiple_of(3) {
+        zchars.push(5);
+    }
+    let mut result = Vec::new();
+    for (i, chunk) in zchars.chunks(3).enumerate() {
+        let mut word = u16::from(chunk[0]) << 10 | u16::from(chunk[1]) << 5 | u16::from(chunk[2]);
+        if i == zchars.len() / 3 - 1 {
+            word |= 0x8000;
+        }
+        result.extend(&word.to_be_bytes());
+    }
+    result
+}
+
+/// The header of a story with this `layout`, zeroed up to the code
+///
+/// The dictionary is empty if it lies before the code, else it is left to the caller.
+pub fn story(layout: Layout) -> Vec<u8> {
+    let mut data = vec![0; layout.code];
+    data[0x00] = layout.version;
+    set(&mut data, 0x04, layout.code);
+    set(&mut data, 0x06, layout.code);
+    set(&mut data, 0x08, layout.dict);
+    set(&mut data, 0x0a, layout.obj);
+    set(&mut data, 0x0c, layout.globals);
+    set(&mut data, 0x0e, layout.stat);
+    if layout.dict + 4 <= layout.code {
+        data[layout.dict + 1] = 9;
+    }
+    data
+}
+
+/// A story with the default layout, running `code`
+pub fn story_with(code: &[u8]) -> Vec<u8> {
+    let mut data = story(LAYOUT);
+    data.extend(code);
+    data
+}
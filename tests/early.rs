@@ -0,0 +1,123 @@
+mod common;
+use common::{pack, set, Layout};
+
+// Layout of the stories
+const ABBR: usize = 0x40;
+const OBJ: usize = 0x80;
+const GLOBALS: usize = 0xca;
+const TEXT: usize = 0x2aa;
+const PARSE: usize = 0x2c0;
+const DICT: usize = 0x2ca;
+
+/// A story that prints a greeting, reads a line and tells if it's in the dictionary
+fn story(v: u8) -> Vec<u8> {
+    // the code follows the dictionary
+    let mut data = common::story(Layout {
+        version: v,
+        globals: GLOBALS,
+        dict: DICT,
+        obj: OBJ,
+        stat: DICT,
+        code: DICT,
+    });
+
+    // object 1, without name nor properties, shown in the status line
+    set(&mut data, OBJ + 62 + 7, OBJ + 62 + 9);
+    set(&mut data, GLOBALS, 1);
+    data[TEXT] = 20;
+    data[PARSE] = 2;
+
+    // dictionary: "open" and "take"
+    data.extend(&[1, b',', 7, 0, 2]);
+    let open = data.len();
+    data.extend(&pack(vec![20, 21, 10, 19]));
+    data.extend(&[0; 3]);
+    data.extend(&pack(vec![25, 6, 16, 10]));
+    data.extend(&[0; 3]);
+
+    // "the end" (abbreviation 0, in version 2 only)
+    if !data.len().is_multiple_of(2) {
+        data.push(0);
+    }
+    let addr = data.len() / 2;
+    set(&mut data, ABBR, addr);
+    set(&mut data, 0x18, ABBR);
+    data.extend(&pack(vec![25, 13, 10, 0, 10, 19, 9]));
+
+    let newline = if v == 1 { vec![1] } else { vec![3, 7] };
+    let code = data.len();
+    set(&mut data, 0x04, code);
+    set(&mut data, 0x06, code);
+
+    // print "Hello, WORLD!" with shifts and shift-locks, then a newline and "ok"
+    let mut zchars = vec![2, 13, 10, 17, 17, 20, 3];
+    zchars.push(if v == 1 { 18 } else { 19 });
+    zchars.extend(&[0, 4, 28, 20, 23, 17, 9, 2]);
+    zchars.push(if v == 1 { 19 } else { 20 });
+    zchars.push(5);
+    zchars.extend(&newline);
+    zchars.extend(&[20, 16]);
+    if v == 2 {
+        zchars.extend(&[0, 1, 0]);
+    }
+    data.push(0xb2);
+    data.extend(&pack(zchars));
+    data.push(0xbb);
+
+    // sread TEXT PARSE
+    data.extend(&[0xe4, 0x0f]);
+    data.extend(&(TEXT as u16).to_be_bytes());
+    data.extend(&(PARSE as u16).to_be_bytes());
+    // loadw PARSE 1 -> sp
+    data.extend(&[0xcf, 0x1f]);
+    data.extend(&(PARSE as u16).to_be_bytes());
+    data.extend(&[1, 0]);
+    // je sp open ?yes
+    data.extend(&[0xc1, 0x8f, 0]);
+    data.extend(&(open as u16).to_be_bytes());
+    let no = pack([&[19, 20][..], &newline].concat());
+    data.push(0xc0 | (no.len() + 4) as u8);
+    // print "no\n" quit
+    data.push(0xb2);
+    data.extend(&no);
+    data.push(0xba);
+    // yes: print "yes\n" quit
+    data.push(0xb2);
+    data.extend(&pack([&[30, 10, 24][..], &newline].concat()));
+    data.push(0xba);
+
+    if !data.len().is_multiple_of(2) {
+        data.push(0);
+    }
+    let len = data.len() / 2;
+    set(&mut data, 0x1a, len);
+    data
+}
+
+fn main(v: u8, input: &str, expect: &str) {
+    let (str, _, err) = common::main(
+        story(v),
+        vec![(input.to_string(), '\n')].into_iter(),
+        "".chars(),
+    );
+    common::check(&str, expect, true);
+    common::errors(err, Vec::new());
+}
+
+#[test]
+fn v1() {
+    main(1, "OPEN", "Hello, WORLD!\nok\n\nyes\n");
+    main(1, "close", "Hello, WORLD!\nok\n\nno\n");
+    // lowercase, then cut to the 20 chars of the buffer
+    main(
+        1,
+        &format!("{}İ", "A".repeat(19)),
+        "Hello, WORLD!\nok\n\nno\n",
+    );
+}
+
+#[test]
+fn v2() {
+    main(2, "Take", "Hello, WORLD!\nok the end\n\nno\n");
+    main(2, "open", "Hello, WORLD!\nok the end\n\nyes\n");
+}
@@ -0,0 +1,41 @@
+use rzif::{StoryText, TextKind};
+
+fn find<'a>(texts: &'a [StoryText], text: &str) -> &'a StoryText {
+    texts
+        .iter()
+        .find(|story| story.text == text)
+        .unwrap_or_else(|| panic!("{:?} not found", text))
+}
+
+#[test]
+fn czech() {
+    let texts = rzif::extract(include_bytes!("../zcode/czech/czech.z5")).unwrap();
+    assert_eq!(find(&texts, "xyzzy").kind, TextKind::Abbreviation(32));
+    let string = find(&texts, "A long string that Inform will put in high memory");
+    assert_eq!((string.kind, string.addr), (TextKind::String, 0x2ecc));
+    assert_eq!(find(&texts, "I love 'xyzzy' ").addr, 0x2ef0);
+    assert_eq!(find(&texts, " I love 'xyzzy'\n").kind, TextKind::Inline);
+
+    // every string once, sorted by address
+    let strings = texts
+        .iter()
+        .filter(|text| matches!(text.kind, TextKind::Inline | TextKind::String))
+        .map(|text| text.addr)
+        .collect::<Vec<_>>();
+    assert!(strings.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn strictz() {
+    let texts = rzif::extract(include_bytes!("../zcode/strictz/strictz.z5")).unwrap();
+    let words = texts
+        .iter()
+        .filter(|text| text.kind == TextKind::Word)
+        .map(|text| text.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(words, vec!["n", "no", "y", "yes"]);
+    assert_eq!(find(&texts, "(obj1)").kind, TextKind::Object(5));
+    assert!(
+        rzif::to_json(&texts).contains("{\"kind\": \"word\", \"addr\": 1550, \"text\": \"yes\"}")
+    );
+}
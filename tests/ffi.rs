@@ -0,0 +1,49 @@
+#![cfg(unix)]
+
+mod common;
+use std::{env, fs, path::PathBuf, process::Command};
+
+/// A story saving, restoring, then reading and printing a char
+fn story() -> Vec<u8> {
+    common::story_with(&[
+        // save -> g0, print_num g0, new_line
+        0xbe, 0x00, 0xff, 0x10, 0xe6, 0xbf, 0x10, 0xbb, // je g0 2 ?end, restore -> sp
+        0x41, 0x10, 0x02, 0xc6, 0xbe, 0x01, 0xff, 0x00,
+        // end: read_char 1 -> sp, print_char sp, new_line, quit
+        0xf6, 0x7f, 0x01, 0x00, 0xe5, 0xbf, 0x00, 0xbb, 0xba,
+    ])
+}
+
+/// Compiles the C harness against the library built with the tests
+fn harness() -> PathBuf {
+    // the tests are in target/<profile>/deps, next to the library they were built with
+    let exe = env::current_exe().unwrap();
+    let dir = exe.parent().unwrap();
+    let tmp = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let harness = tmp.join("harness");
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ffi/harness.c"))
+        .arg(concat!("-I", env!("CARGO_MANIFEST_DIR"), "/include"))
+        .arg(format!("-L{}", dir.display()))
+        .arg(format!("-Wl,-rpath,{}", dir.display()))
+        .args(["-lrzif", "-Wall", "-Werror", "-o"])
+        .arg(&harness)
+        .status()
+        .expect("a C compiler is needed to test the C interface");
+    assert!(status.success());
+    harness
+}
+
+#[test]
+fn c() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi.z5");
+    fs::write(&path, story()).unwrap();
+    let output = Command::new(harness())
+        .arg(&path)
+        .arg("x")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n2\nx\n");
+    assert_eq!(output.status.code(), Some(0));
+}
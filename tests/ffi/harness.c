@@ -0,0 +1,89 @@
+/* Plays a story through the C interface, reading chars from the arguments */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "rzif.h"
+
+typedef struct {
+    uint8_t *saved;
+    size_t saved_len;
+    const char *chars;
+} Harness;
+
+static void write_screen(void *user, const uint8_t *text, size_t len, bool fixed) {
+    (void)user;
+    (void)fixed;
+    fwrite(text, 1, len, stdout);
+}
+
+static uint32_t read_char(void *user, uint16_t time, RzifInterruptChar interrupt, void *data) {
+    Harness *harness = user;
+    (void)time;
+    (void)interrupt;
+    (void)data;
+    return *harness->chars ? (uint32_t)*harness->chars++ : '\n';
+}
+
+static bool save(void *user, const uint8_t *data, size_t len) {
+    Harness *harness = user;
+    free(harness->saved);
+    harness->saved = malloc(len);
+    memcpy(harness->saved, data, len);
+    harness->saved_len = len;
+    return true;
+}
+
+static const uint8_t *restore(void *user, size_t *len) {
+    Harness *harness = user;
+    *len = harness->saved_len;
+    return harness->saved;
+}
+
+static void error(void *user, uint32_t cause, const uint8_t *text, size_t len) {
+    (void)user;
+    fprintf(stderr, "error %u: %.*s\n", cause, (int)len, (const char *)text);
+}
+
+int main(int argc, char **argv) {
+    if (argc != 3) {
+        fprintf(stderr, "Usage: %s path/to/storyfile chars\n", argv[0]);
+        return 3;
+    }
+    FILE *file = fopen(argv[1], "rb");
+    if (!file) {
+        perror(argv[1]);
+        return 3;
+    }
+    static uint8_t story[0x10000];
+    size_t len = fread(story, 1, sizeof story, file);
+    fclose(file);
+
+    Harness harness = {NULL, 0, argv[2]};
+    RzifCallbacks callbacks = {0};
+    callbacks.user = &harness;
+    callbacks.write_screen = write_screen;
+    callbacks.read_char = read_char;
+    callbacks.save = save;
+    callbacks.restore = restore;
+    callbacks.error = error;
+    RzifConfig config;
+    rzif_default_config(&config);
+    config.has_seed = true;
+    config.seed = 1;
+
+    RzifVm *vm = rzif_new(story, len, &config, &callbacks);
+    int32_t outcome = rzif_run(vm);
+    if (outcome == RZIF_FATAL) {
+        uint8_t message[256];
+        size_t message_len = rzif_error(vm, message, sizeof message);
+        if (message_len > sizeof message) {
+            message_len = sizeof message;
+        }
+        fprintf(stderr, "fatal: %.*s\n", (int)message_len, (const char *)message);
+    }
+    rzif_free(vm);
+    free(harness.saved);
+    return outcome;
+}
@@ -0,0 +1,83 @@
+mod common;
+use common::{pack, set, Layout, LAYOUT};
+
+// Layout of the story, as large as version 8 allows
+const ABBR: usize = 0x220;
+const DICT: usize = 0x2e0;
+const OBJ: usize = 0x2e4;
+const ABBR_STRING: usize = 0x1fff0;
+const STRING: usize = 0x7fe00;
+const ROUTINE: usize = 0x7ff00;
+const LEN: usize = 0x7fff8;
+
+fn write(data: &mut [u8], addr: usize, bytes: &[u8]) {
+    data[addr..addr + bytes.len()].copy_from_slice(bytes);
+}
+
+/// A story calling a routine and printing strings at the end of its 512K
+fn story() -> Vec<u8> {
+    let mut data = common::story(Layout {
+        version: 8,
+        dict: DICT,
+        obj: OBJ,
+        ..LAYOUT
+    });
+    data.resize(LEN, 0);
+    set(&mut data, 0x18, ABBR);
+    set(&mut data, 0x1a, LEN / 8);
+
+    // the word before 0x100, read with a negative index
+    set(&mut data, 0xfe, 1234);
+    // all abbreviations are "beyond", above 64K
+    for i in 0..96 {
+        set(&mut data, ABBR + 2 * i, ABBR_STRING / 2);
+    }
+    write(&mut data, ABBR_STRING, &pack(vec![7, 10, 30, 20, 19, 9]));
+    // "far " followed by abbreviation 0
+    write(&mut data, STRING, &pack(vec![11, 6, 23, 0, 1, 0]));
+
+    // call_vs ROUTINE -> sp, print_num sp, new_line
+    // loadw $100 -1 -> sp, print_num sp, new_line, quit
+    let mut code = vec![0xe0, 0x3f];
+    code.extend(&((ROUTINE / 8) as u16).to_be_bytes());
+    code.extend(&[0x00, 0xe6, 0xbf, 0x00, 0xbb]);
+    code.extend(&[0xcf, 0x0f, 0x01, 0x00, 0xff, 0xff, 0x00]);
+    code.extend(&[0xe6, 0xbf, 0x00, 0xbb, 0xba]);
+    write(&mut data, LAYOUT.code, &code);
+
+    // no locals, print_paddr STRING, new_line, jump over 2 bytes
+    let mut code = vec![0x00, 0x8d];
+    code.extend(&((STRING / 8) as u16).to_be_bytes());
+    code.extend(&[0xbb, 0x8c, 0x00, 0x04, 0xff, 0xff]);
+    // loop: print "x", inc_chk g0 2 ?end, jump loop
+    let start = code.len();
+    code.push(0xb2);
+    code.extend(&pack(vec![29]));
+    code.extend(&[0x05, 0x10, 0x02, 0xc5]);
+    let offset = start as isize - (code.len() as isize + 3) + 2;
+    code.push(0x8c);
+    code.extend(&(offset as i16).to_be_bytes());
+    // end: ret 42
+    code.extend(&[0x9b, 42]);
+    write(&mut data, ROUTINE, &code);
+
+    let checksum = data[0x40..]
+        .iter()
+        .fold(0u16, |sum, &byte| sum.wrapping_add(byte.into()));
+    set(&mut data, 0x1c, checksum.into());
+    data
+}
+
+#[test]
+fn v8() {
+    let (str, _, err) = common::main(story(), Vec::new().into_iter(), Vec::new().into_iter());
+    common::check(&str, "far beyond\nxxx42\n1234\n", true);
+    common::errors(err, Vec::new());
+}
+
+#[test]
+fn validate() {
+    assert!(rzif::validate(&story()).unwrap().is_empty());
+    let info = rzif::info(&story()).unwrap();
+    assert_eq!((info.version, info.abbreviations), (8, 96));
+}
@@ -0,0 +1,71 @@
+mod common;
+use common::set;
+use rzif::{Cause, ErrorLevel, ErrorPolicy};
+
+// Extension table of the story
+const EXT: usize = 0x300;
+
+/// Plays strictz, with its 17 errors on object 0
+fn strictz(chars: &str, error: ErrorPolicy) -> (String, Vec<rzif::Error>) {
+    let (str, _, err) = common::main_with(
+        include_bytes!("../zcode/strictz/strictz.z5").to_vec(),
+        vec![("n".to_string(), '\n')].into_iter(),
+        chars.chars(),
+        error,
+    );
+    (str, err)
+}
+
+#[test]
+fn presets() {
+    let out = include_str!("../zcode/strictz/strictz.out");
+    let (str, err) = strictz(" ", ErrorPolicy::forgiving());
+    common::check(&str, out, true);
+    common::errors(err, Vec::new());
+
+    let (str, err) = strictz(
+        " ",
+        ErrorPolicy::new(ErrorLevel::Always).with_level(Cause::BadObj, ErrorLevel::Once),
+    );
+    common::check(&str, out, true);
+    common::errors(err, vec![(Cause::BadObj, (0, 0))]);
+
+    // Frotz warns about the first one and keeps playing
+    let (str, err) = strictz(" ", ErrorPolicy::frotz());
+    common::check(&str, out, true);
+    common::errors(err, vec![(Cause::BadObj, (0, 0))]);
+}
+
+/// A story running `code`, with a unicode table out of memory
+fn story(code: &[u8]) -> Vec<u8> {
+    let mut data = common::story_with(code);
+    set(&mut data, 0x36, EXT);
+    set(&mut data, EXT, 3);
+    set(&mut data, EXT + 6, 0xfff0);
+    data
+}
+
+fn main(code: &[u8], chars: &str, error: ErrorPolicy) -> (String, Vec<rzif::Error>) {
+    let (str, _, err) =
+        common::main_with(story(code), Vec::new().into_iter(), chars.chars(), error);
+    (str, err)
+}
+
+#[test]
+fn fallback() {
+    // div 1 0 -> sp, print_num sp, quit
+    let code = [0xd7, 0x5f, 0x01, 0x00, 0x00, 0xe6, 0xbf, 0x00, 0xba];
+    let error = ErrorPolicy::new(ErrorLevel::Never);
+    let (str, err) = main(&code, "", error);
+    common::check(&str, "0", true);
+    common::errors(err, Vec::new());
+    let (str, err) = main(&code, "", error.with_fallback(Cause::DivByZero, 7));
+    common::check(&str, "7", true);
+    common::errors(err, Vec::new());
+
+    // read_char 1 -> sp, print_char sp, quit
+    let code = [0xf6, 0x7f, 0x01, 0x00, 0xe5, 0xbf, 0x00, 0xba];
+    let (str, err) = main(&code, "é", ErrorPolicy::forgiving());
+    common::check(&str, "?", true);
+    common::errors(err, Vec::new());
+}
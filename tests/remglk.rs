@@ -0,0 +1,239 @@
+mod common;
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+// Layout of the story
+const TEXT: u16 = 0x300;
+const ROUTINE: usize = 0x500;
+
+/// A story running `code`
+fn story(code: &[u8]) -> Vec<u8> {
+    let mut data = common::story_with(code);
+    data[TEXT as usize] = 20;
+    data
+}
+
+/// Plays the `story` with the `events`, returning the updates
+fn play(name: &str, story: Vec<u8>, events: &str) -> Vec<String> {
+    play_in(
+        &PathBuf::from(env!("CARGO_TARGET_TMPDIR")),
+        name,
+        story,
+        events,
+    )
+}
+
+/// Plays the `story` with the `events` from the directory `dir`, returning the updates
+fn play_in(dir: &PathBuf, name: &str, story: Vec<u8>, events: &str) -> Vec<String> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, story).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rzif"))
+        .arg("remglk")
+        .arg(&path)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(events.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Writes to both windows, then reads a line and a char
+#[test]
+fn input() {
+    // split_window 1, set_window 1, print_char 'S', set_window 0, print_num 1, new_line
+    let mut code = vec![
+        0xea, 0x7f, 0x01, 0xeb, 0x7f, 0x01, 0xe5, 0x7f, b'S', 0xeb, 0x7f, 0x00, 0xe6, 0x7f, 0x01,
+        0xbb,
+    ];
+    // aread TEXT 0 -> sp, loadb TEXT 2 -> sp, print_char sp, new_line
+    code.extend(&[0xe4, 0x1f]);
+    code.extend(&TEXT.to_be_bytes());
+    code.extend(&[0x00, 0x00, 0xd0, 0x1f]);
+    code.extend(&TEXT.to_be_bytes());
+    code.extend(&[0x02, 0x00, 0xe5, 0xbf, 0x00, 0xbb]);
+    // read_char 1 -> sp, print_char sp, quit
+    code.extend(&[0xf6, 0x7f, 0x01, 0x00, 0xe5, 0xbf, 0x00, 0xba]);
+
+    let updates = play(
+        "input.z5",
+        story(&code),
+        concat!(
+            r#"{"type": "init", "gen": 0, "metrics": {"width": 40, "height": 10}}"#,
+            "\n",
+            r#"{"type": "arrange", "gen": 1, "metrics": {"width": 20, "height": 10}}"#,
+            "\n",
+            // outdated, ignored
+            r#"{"type": "line", "gen": 1, "window": 1, "value": "no"}"#,
+            "\n",
+            r#"{"type": "line", "gen": 2, "window": 1, "value": "Look"}"#,
+            "\n",
+            r#"{"type": "char", "gen": 3, "window": 1, "value": "return"}"#,
+            "\n",
+        ),
+    );
+    let windows = |width, height| {
+        format!(
+            concat!(
+                r#""windows":[{{"id":1,"type":"buffer","rock":0,"left":0,"top":1,"width":{0},"height":{1}}},"#,
+                r#"{{"id":2,"type":"grid","rock":0,"gridwidth":{0},"gridheight":1,"left":0,"top":0,"width":{0},"height":1}}]"#
+            ),
+            width,
+            height - 1
+        )
+    };
+    let grid = |width: usize| {
+        format!(
+            r#"{{"id":2,"lines":[{{"line":0,"content":[{{"style":"normal","text":"S{}"}}]}}]}}"#,
+            " ".repeat(width - 1)
+        )
+    };
+    assert_eq!(
+        updates,
+        vec![
+            format!(
+                concat!(
+                    r#"{{"type":"update","gen":1,{},"content":["#,
+                    r#"{{"id":1,"text":[{{"append":true,"content":[{{"style":"normal","text":"1"}}]}},{{}}]}},{}],"#,
+                    r#""input":[{{"id":1,"gen":1,"type":"line","maxlen":20}}]}}"#
+                ),
+                windows(40, 10),
+                grid(40)
+            ),
+            format!(
+                concat!(
+                    r#"{{"type":"update","gen":2,{},"content":[{}],"#,
+                    r#""input":[{{"id":1,"gen":1,"type":"line","maxlen":20}}]}}"#
+                ),
+                windows(20, 10),
+                grid(20)
+            ),
+            concat!(
+                r#"{"type":"update","gen":3,"content":[{"id":1,"text":["#,
+                r#"{"append":true,"content":[{"style":"input","text":"Look"}]},"#,
+                r#"{"content":[{"style":"normal","text":"l"}]},{}]}],"#,
+                r#""input":[{"id":1,"gen":3,"type":"char"}]}"#
+            )
+            .to_string(),
+            concat!(
+                r#"{"type":"update","gen":4,"content":[{"id":1,"text":[{}]}],"#,
+                r#""input":[],"exit":true}"#
+            )
+            .to_string(),
+        ]
+    );
+}
+
+/// Reads a line interrupted by a timed routine
+#[test]
+fn timer() {
+    // aread TEXT 0 10 ROUTINE -> sp, print_num sp, loadb TEXT 2 -> sp, print_char sp, quit
+    let mut code = vec![0xe4, 0x14];
+    code.extend(&TEXT.to_be_bytes());
+    code.extend(&[0x00, 0x0a]);
+    code.extend(&((ROUTINE / 4) as u16).to_be_bytes());
+    code.extend(&[0x00, 0xe6, 0xbf, 0x00, 0xd0, 0x1f]);
+    code.extend(&TEXT.to_be_bytes());
+    code.extend(&[0x02, 0x00, 0xe5, 0xbf, 0x00, 0xba]);
+    let mut story = story(&code);
+    // ROUTINE: print_char 'T', rtrue
+    story.resize(ROUTINE, 0);
+    story.extend(&[0x00, 0xe5, 0x7f, b'T', 0xb0]);
+
+    let updates = play(
+        "timer.z5",
+        story,
+        concat!(
+            r#"{"type": "init", "gen": 0, "metrics": {"width": 40, "height": 10}}"#,
+            r#"{"type": "timer", "gen": 1, "partial": {"1": "ab"}}"#,
+        ),
+    );
+    assert_eq!(
+        updates,
+        vec![
+            concat!(
+                r#"{"type":"update","gen":1,"#,
+                r#""windows":[{"id":1,"type":"buffer","rock":0,"left":0,"top":0,"width":40,"height":10}],"#,
+                r#""input":[{"id":1,"gen":1,"type":"line","maxlen":20}],"timer":1000}"#
+            ),
+            concat!(
+                r#"{"type":"update","gen":2,"content":[{"id":1,"text":[{"append":true,"content":[{"style":"normal","text":"T0a"}]}]}],"#,
+                r#""input":[],"timer":null,"exit":true}"#
+            ),
+        ]
+    );
+}
+
+/// Saves in the current directory, whatever the name given by the client
+#[test]
+fn files() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("remglk");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    // save -> sp, print_num sp, quit
+    let code = [0xbe, 0x00, 0xff, 0x00, 0xe6, 0xbf, 0x00, 0xba];
+
+    let updates = play_in(
+        &dir,
+        "files.z5",
+        story(&code),
+        concat!(
+            r#"{"type": "init", "gen": 0, "metrics": {"width": 40, "height": 10}}"#,
+            r#"{"type": "specialresponse", "gen": 1, "response": "fileref_prompt", "value": "../escape"}"#,
+        ),
+    );
+    assert!(updates.last().unwrap().contains(r#""text":"1""#));
+    assert!(dir.join("escape").is_file());
+    assert!(!dir.join("../escape").exists());
+}
+
+/// The timed routine prints while the player is typing
+#[test]
+fn redraw() {
+    // aread TEXT 0 10 ROUTINE -> sp, quit
+    let mut code = vec![0xe4, 0x14];
+    code.extend(&TEXT.to_be_bytes());
+    code.extend(&[0x00, 0x0a]);
+    code.extend(&((ROUTINE / 4) as u16).to_be_bytes());
+    code.extend(&[0x00, 0xba]);
+    let mut story = story(&code);
+    // ROUTINE: print_char 'T', rfalse
+    story.resize(ROUTINE, 0);
+    story.extend(&[0x00, 0xe5, 0x7f, b'T', 0xb1]);
+
+    let updates = play(
+        "redraw.z5",
+        story,
+        concat!(
+            r#"{"type": "init", "gen": 0, "metrics": {"width": 40, "height": 10}}"#,
+            r#"{"type": "timer", "gen": 1, "partial": {"1": "ab"}}"#,
+            r#"{"type": "line", "gen": 2, "window": 1, "value": "abc"}"#,
+        ),
+    );
+    // the text typed so far is written again after the output of the routine
+    assert_eq!(
+        updates[1],
+        concat!(
+            r#"{"type":"update","gen":2,"content":[{"id":1,"text":[{"append":true,"content":[{"style":"normal","text":"Tab"}]}]}],"#,
+            r#""input":[{"id":1,"gen":1,"type":"line","maxlen":20}]}"#
+        )
+    );
+}
@@ -0,0 +1,112 @@
+extern crate rzif;
+use rzif::Cause;
+
+fn main(file: Vec<u8>) {
+    let transcript = rzif::replay(file.clone(), "# strictz\nn\n \n", 1234);
+    let output = transcript
+        .turns
+        .iter()
+        .map(|turn| turn.output.as_str())
+        .collect::<String>();
+    assert_eq!(output, include_str!("../zcode/strictz/strictz.out"));
+    assert_eq!(transcript.turns.len(), 3);
+    assert_eq!(transcript.turns[0].command, None);
+    assert_eq!(transcript.turns[1].command, Some("n".to_string()));
+    assert_eq!(transcript.turns[2].command, Some(" ".to_string()));
+    assert_eq!(transcript.errors.len(), 18);
+    assert_eq!(transcript.errors.last().unwrap().cause, Cause::Quit);
+
+    let golden = transcript.to_string();
+    assert_eq!(rzif::replay(file, "n\n \n", 1234).diff(&golden), None);
+
+    let offset = golden.find("(Y/N) n\n").unwrap() + 8;
+    let mut changed = golden.clone();
+    changed.insert(offset, '!');
+    let diff = transcript.diff(&changed).unwrap();
+    assert_eq!(diff.turn, 1);
+    assert_eq!(diff.command, Some("n".to_string()));
+    assert_eq!(diff.line, golden[..offset].matches('\n').count() + 1);
+    assert_ne!(diff.expected, diff.found);
+
+    let diff = transcript.diff(&golden[..golden.len() - 1]).unwrap();
+    assert_eq!(diff.turn, 2);
+    let diff = transcript.diff(&(golden.clone() + "more")).unwrap();
+    assert_eq!(diff.turn, 2);
+    assert!(diff.expected.ends_with(" more"));
+}
+
+#[test]
+fn v5() {
+    main(include_bytes!("../zcode/strictz/strictz.z5").to_vec());
+}
+
+#[test]
+fn v8() {
+    main(include_bytes!("../zcode/strictz/strictz.z8").to_vec());
+}
+
+#[test]
+fn stopped() {
+    let transcript = rzif::replay(
+        include_bytes!("../zcode/strictz/strictz.z5").to_vec(),
+        "",
+        0,
+    );
+    assert_eq!(transcript.turns.len(), 1);
+    assert_eq!(transcript.errors.last().unwrap().cause, Cause::Stopped);
+}
+
+#[test]
+fn context() {
+    let transcript = rzif::replay(
+        include_bytes!("../zcode/strictz/strictz.z5").to_vec(),
+        "n\n \n",
+        0,
+    );
+    let error = &transcript.errors[0];
+    let instr = error.instr.as_ref().unwrap();
+    assert_eq!(instr.name, "jin");
+    assert_eq!(instr.operands, vec![0, 5]);
+    assert_eq!(error.calls.len(), 2);
+    assert_eq!(error.calls[0].routine, Some(0x620));
+    assert_eq!(error.calls[0].locals, vec![0, 5, 0]);
+    assert_eq!(
+        error.to_string(),
+        "Error: object 0 is not valid\n  while executing instruction at $621\n  instruction: jin $0000 $0005\n  in routine $620, locals: $0000 $0005 $0000\n  in routine $96c, locals: $0000\n  in the main routine"
+    );
+}
+
+#[test]
+fn profile() {
+    let file = include_bytes!("../zcode/czech/czech.z5").to_vec();
+    let profile = rzif::profile(file.clone(), "", 1);
+    assert_eq!(
+        profile.transcript.to_string(),
+        rzif::replay(file, "", 1).to_string()
+    );
+    assert_eq!(
+        profile.instructions,
+        profile.opcodes.iter().map(|opcode| opcode.1).sum::<u64>()
+    );
+    assert!(profile.instructions > 10000);
+    assert!(profile
+        .opcodes
+        .windows(2)
+        .all(|pair| pair[0].2 >= pair[1].2));
+    assert!(profile.opcodes.iter().any(|opcode| opcode.0 == "call_vs"));
+}
+
+#[test]
+fn comments() {
+    let file = include_bytes!("../zcode/gntests/gntests.z5").to_vec();
+    let transcript = rzif::replay(file, "# menu\n1\n#\n", 1);
+    let commands: Vec<_> = transcript
+        .turns
+        .iter()
+        .map(|turn| turn.command.clone())
+        .collect();
+    assert_eq!(
+        commands,
+        [None, Some("1".to_string()), Some("#".to_string())]
+    );
+}
@@ -0,0 +1,116 @@
+mod common;
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+};
+
+// Layout of the story
+const TEXT: u16 = 0x300;
+
+/// A story printing the first char of a line, then saving
+fn story() -> Vec<u8> {
+    // aread TEXT 0 -> sp, loadb TEXT 2 -> sp, print_char sp, new_line
+    let mut code = vec![0xe4, 0x1f];
+    code.extend(&TEXT.to_be_bytes());
+    code.extend(&[0x00, 0x00, 0xd0, 0x1f]);
+    code.extend(&TEXT.to_be_bytes());
+    code.extend(&[0x02, 0x00, 0xe5, 0xbf, 0x00, 0xbb]);
+    // save -> sp, print_num sp, new_line, quit
+    code.extend(&[0xbe, 0x00, 0xff, 0x00, 0xe6, 0xbf, 0x00, 0xbb, 0xba]);
+
+    let mut data = common::story_with(&code);
+    data[TEXT as usize] = 20;
+    data
+}
+
+/// The server, killed when dropped
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Starts a server saving in `saves`, for at most `max` sessions, returning its address
+///
+/// The storyfile is written next to the saves, so that the tests don't share it.
+fn serve(saves: &PathBuf, idle: &str, max: &str) -> (Server, String) {
+    let path = saves.with_extension("z5");
+    fs::write(&path, story()).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rzif"))
+        .arg("serve")
+        .arg(&path)
+        .arg("127.0.0.1:0")
+        .arg(saves)
+        .arg(idle)
+        .arg(max)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line
+        .trim_end()
+        .strip_prefix("Listening on ")
+        .unwrap()
+        .to_string();
+    (Server(child), address)
+}
+
+fn read(mut stream: TcpStream) -> String {
+    let mut text = String::new();
+    stream.read_to_string(&mut text).unwrap();
+    text
+}
+
+#[test]
+fn sessions() {
+    let saves = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("saves");
+    let _ = fs::remove_dir_all(&saves);
+    let (_server, address) = serve(&saves, "60", "10");
+
+    let mut alice = TcpStream::connect(&address).unwrap();
+    let mut bob = TcpStream::connect(&address).unwrap();
+    // telnet negotiations are ignored
+    alice.write_all(b"alice\r\n\xff\xfb\x1fabc\r\n").unwrap();
+    bob.write_all(b"bob\r\nxyz\r\n").unwrap();
+    bob.write_all(b"../game\r\n").unwrap();
+    alice.write_all(b"game\r\n").unwrap();
+    assert_eq!(read(alice), "Your name: a\r\n\r\nEnter file name: 1\r\n");
+    assert_eq!(read(bob), "Your name: x\r\n\r\nEnter file name: 1\r\n");
+    assert!(saves.join("alice/game").is_file());
+    assert!(saves.join("bob/game").is_file());
+    assert!(!saves.join("game").exists());
+}
+
+#[test]
+fn idle() {
+    let saves = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("idle");
+    let (_server, address) = serve(&saves, "1", "10");
+
+    let stream = TcpStream::connect(&address).unwrap();
+    assert_eq!(
+        read(stream),
+        "Your name: \r\nIdle for too long, goodbye\r\n"
+    );
+}
+
+#[test]
+fn limits() {
+    let saves = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("limits");
+    let (_server, address) = serve(&saves, "60", "1");
+
+    let mut alice = TcpStream::connect(&address).unwrap();
+    let bob = TcpStream::connect(&address).unwrap();
+    assert_eq!(read(bob), "Too many players, try again later\r\n");
+    // a line too long closes the session
+    alice.write_all(&[b'a'; 4096]).unwrap();
+    assert_eq!(read(alice), "Your name: ");
+}
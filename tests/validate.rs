@@ -0,0 +1,59 @@
+use rzif::Cause;
+
+fn causes(file: &[u8]) -> Vec<Cause> {
+    rzif::validate(file)
+        .unwrap()
+        .iter()
+        .map(|error| error.cause)
+        .collect()
+}
+
+#[test]
+fn valid() {
+    for file in [
+        &include_bytes!("../zcode/czech/czech.z3")[..],
+        include_bytes!("../zcode/czech/czech.z4"),
+        include_bytes!("../zcode/czech/czech.z5"),
+        include_bytes!("../zcode/czech/czech.z7"),
+        include_bytes!("../zcode/czech/czech.z8"),
+        include_bytes!("../zcode/etude/etude.z5"),
+        include_bytes!("../zcode/gntests/gntests.z5"),
+        include_bytes!("../zcode/strictz/strictz.z5"),
+        include_bytes!("../zcode/unicode/unicode.z5"),
+    ] {
+        assert_eq!(causes(file), Vec::new());
+    }
+}
+
+#[test]
+fn unicode() {
+    // this version 4 storyfile uses opcodes and chars of version 5
+    let errors = rzif::validate(include_bytes!("../zcode/unicode/unicode.z4")).unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].cause, Cause::BadZSCIIChar);
+    assert_eq!(errors[0].data, (226, 0));
+    assert_eq!(errors[1].cause, Cause::BadOpcode);
+    assert_eq!(errors[1].data, (0, 0x0e));
+    assert_eq!(
+        format!("{}", errors[1].trace.last().unwrap()),
+        "calling routine at $7ac"
+    );
+}
+
+#[test]
+fn corrupted() {
+    let mut file = include_bytes!("../zcode/czech/czech.z5").to_vec();
+    file[0x1c] ^= 0xff;
+    assert_eq!(causes(&file), vec![Cause::BadChecksum]);
+
+    // cut the storyfile in the dictionary
+    let mut file = include_bytes!("../zcode/czech/czech.z5").to_vec();
+    let dict = (file[0x08] as usize) << 8 | file[0x09] as usize;
+    file.truncate(dict + 4);
+    file[0x1a] = 0;
+    file[0x1b] = 0;
+    assert!(causes(&file).contains(&Cause::DictOut));
+
+    let file = include_bytes!("../zcode/etude/etude.z6");
+    assert_eq!(rzif::validate(file).unwrap_err().cause, Cause::BadVer);
+}